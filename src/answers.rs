@@ -0,0 +1,188 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::solution::Answer;
+
+/// The file next to the puzzle inputs holding their expected answers.
+pub const DEFAULT_ANSWERS_FILE: &str = "answers.toml";
+
+/// Expected answers by day and part, read from a TOML file of the form
+///
+/// ```toml
+/// [day1]
+/// part1 = 54390
+/// part2 = 54277
+/// ```
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Answers(BTreeMap<(u32, u32), Answer>);
+
+/// An error in a file of `[dayN]` tables, with the 1-based line if it is about
+/// the contents of the file.
+#[derive(Debug, PartialEq, Eq)]
+pub struct FileError {
+    pub path: PathBuf,
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for FileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{}: {}", self.path.display(), line, self.message),
+            None => write!(f, "{}: {}", self.path.display(), self.message),
+        }
+    }
+}
+
+impl std::error::Error for FileError {}
+
+fn table_day(line: &str) -> Option<u32> {
+    line.strip_prefix("[day")?.strip_suffix(']')?.parse().ok()
+}
+
+/// Calls `f` with the day, key and unquoted value of every `key = value` entry
+/// in the `[dayN]` tables of `text`. Returns the 1-based line and a message on
+/// error, including the errors returned by `f`.
+pub fn parse_entries(
+    text: &str,
+    mut f: impl FnMut(u32, &str, &str) -> Result<(), String>,
+) -> Result<(), (usize, String)> {
+    let mut day = None;
+    for (index, line) in text.lines().enumerate() {
+        let error = |message: String| (index + 1, message);
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+        if line.starts_with('[') {
+            day =
+                Some(table_day(line).ok_or_else(|| {
+                    error(format!("expected a table like [day1], found '{}'", line))
+                })?);
+            continue;
+        }
+        let Some(day) = day else {
+            return Err(error(
+                "expected a [dayN] table before the first entry".to_owned(),
+            ));
+        };
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| error(format!("expected 'key = <value>', found '{}'", line)))?;
+        f(day, key.trim(), value.trim().trim_matches('"')).map_err(error)?;
+    }
+    Ok(())
+}
+
+/// Reads a file of `[dayN]` tables, see [`parse_entries`].
+pub fn load_entries(
+    path: &Path,
+    f: impl FnMut(u32, &str, &str) -> Result<(), String>,
+) -> Result<(), FileError> {
+    let text = std::fs::read_to_string(path).map_err(|e| FileError {
+        path: path.to_owned(),
+        line: None,
+        message: e.to_string(),
+    })?;
+    parse_entries(&text, f).map_err(|(line, message)| FileError {
+        path: path.to_owned(),
+        line: Some(line),
+        message,
+    })
+}
+
+fn key_part(key: &str) -> Option<u32> {
+    key.strip_prefix("part")?.parse().ok()
+}
+
+impl Answers {
+    pub fn load(path: &Path) -> Result<Answers, FileError> {
+        let mut answers = Answers::default();
+        load_entries(path, |day, key, value| answers.insert(day, key, value))?;
+        Ok(answers)
+    }
+
+    #[cfg(test)]
+    fn parse(text: &str) -> Result<Answers, (usize, String)> {
+        let mut answers = Answers::default();
+        parse_entries(text, |day, key, value| answers.insert(day, key, value))?;
+        Ok(answers)
+    }
+
+    fn insert(&mut self, day: u32, key: &str, value: &str) -> Result<(), String> {
+        let part =
+            key_part(key).ok_or_else(|| format!("expected a key like part1, found '{}'", key))?;
+        let value = value
+            .parse()
+            .map_err(|_| format!("expected an integer answer, found '{}'", value))?;
+        if self.0.insert((day, part), Answer(value)).is_some() {
+            return Err(format!("duplicate answer for day {} part {}", day, part));
+        }
+        Ok(())
+    }
+
+    pub fn get(&self, day: u32, part: u32) -> Option<Answer> {
+        self.0.get(&(day, part)).copied()
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Verdict {
+    Pass,
+    Fail { expected: Answer, actual: Answer },
+    Missing { actual: Answer },
+}
+
+pub fn check(expected: Option<Answer>, actual: Answer) -> Verdict {
+    match expected {
+        Some(expected) if expected == actual => Verdict::Pass,
+        Some(expected) => Verdict::Fail { expected, actual },
+        None => Verdict::Missing { actual },
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_answers() {
+        let answers = Answers::parse(
+            "# Expected answers\n[day1]\npart1 = 54390\npart2 = \"54277\" # quoted\n\n[day25]\npart1 = 514794\n",
+        )
+        .unwrap();
+        assert_eq!(answers.get(1, 1), Some(Answer(54390)));
+        assert_eq!(answers.get(1, 2), Some(Answer(54277)));
+        assert_eq!(answers.get(25, 1), Some(Answer(514794)));
+        assert_eq!(answers.get(25, 2), None);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(Answers::parse("part1 = 1").unwrap_err().0, 1);
+        assert_eq!(Answers::parse("[day1]\npart1 = x").unwrap_err().0, 2);
+        assert_eq!(Answers::parse("[dayx]").unwrap_err().0, 1);
+        assert_eq!(Answers::parse("[day1]\nanswer = 1").unwrap_err().0, 2);
+        assert_eq!(
+            Answers::parse("[day1]\npart1 = 1\npart1 = 2").unwrap_err(),
+            (3, "duplicate answer for day 1 part 1".to_owned())
+        );
+    }
+
+    #[test]
+    fn verdicts() {
+        assert_eq!(check(Some(Answer(1)), Answer(1)), Verdict::Pass);
+        assert_eq!(
+            check(Some(Answer(1)), Answer(2)),
+            Verdict::Fail {
+                expected: Answer(1),
+                actual: Answer(2)
+            }
+        );
+        assert_eq!(
+            check(None, Answer(2)),
+            Verdict::Missing { actual: Answer(2) }
+        );
+    }
+}
@@ -0,0 +1,334 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::answers::{self, FileError};
+use crate::error::Error;
+use crate::input::InputSource;
+use crate::registry::Task;
+use crate::solution::{DayResult, Extras};
+
+/// A timed step of a day: parsing the input or solving one part.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Step {
+    Parse,
+    Part(u32),
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Step::Parse => f.pad("parse"),
+            Step::Part(part) => f.pad(&format!("part{}", part)),
+        }
+    }
+}
+
+fn parse_step(key: &str) -> Option<Step> {
+    match key {
+        "parse" => Some(Step::Parse),
+        _ => key.strip_prefix("part")?.parse().ok().map(Step::Part),
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub mean: Duration,
+    /// The sample standard deviation, zero for a single sample.
+    pub stddev: Duration,
+}
+
+impl Stats {
+    /// The statistics of a non-empty list of samples.
+    pub fn new(samples: &[Duration]) -> Stats {
+        let mut sorted = samples.to_vec();
+        sorted.sort();
+        let n = sorted.len();
+        let median = if n % 2 == 1 {
+            sorted[n / 2]
+        } else {
+            (sorted[n / 2 - 1] + sorted[n / 2]) / 2
+        };
+
+        let mean = sorted.iter().sum::<Duration>() / n as u32;
+        let variance = if n > 1 {
+            let mean = mean.as_secs_f64();
+            sorted
+                .iter()
+                .map(|s| (s.as_secs_f64() - mean).powi(2))
+                .sum::<f64>()
+                / (n - 1) as f64
+        } else {
+            0.0
+        };
+
+        Stats {
+            min: sorted[0],
+            median,
+            mean,
+            stddev: Duration::from_secs_f64(variance.sqrt()),
+        }
+    }
+}
+
+pub struct Measurement {
+    pub day: u32,
+    pub step: Step,
+    pub stats: Stats,
+}
+
+/// Turns a failed part into an error, as a benchmark of a wrong solution is moot.
+fn checked(mut result: DayResult) -> Result<DayResult, Error> {
+    if let Some(index) = result.parts.iter().position(|p| p.answer.is_err()) {
+        return Err(result.parts.swap_remove(index).answer.unwrap_err());
+    }
+    Ok(result)
+}
+
+/// Runs `task` `warmup` times without measuring and then `runs` times, timing the
+/// parse step and every part separately.
+pub fn measure(
+    task: &Task,
+    input: &InputSource,
+    warmup: usize,
+    runs: usize,
+) -> Result<Vec<Measurement>, Error> {
+    for _ in 0..warmup {
+        checked(task.run(input, Extras::default())?)?;
+    }
+
+    let mut samples: BTreeMap<Step, Vec<Duration>> = BTreeMap::new();
+    for _ in 0..runs {
+        let result = checked(task.run(input, Extras::default())?)?;
+        samples
+            .entry(Step::Parse)
+            .or_default()
+            .push(result.parse_time);
+        for part in &result.parts {
+            samples
+                .entry(Step::Part(part.part))
+                .or_default()
+                .push(part.time);
+        }
+    }
+
+    Ok(samples
+        .into_iter()
+        .map(|(step, samples)| Measurement {
+            day: task.day,
+            step,
+            stats: Stats::new(&samples),
+        })
+        .collect())
+}
+
+/// The median time of every step of a previous benchmark, stored as `[dayN]`
+/// tables of nanoseconds.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Baseline(BTreeMap<(u32, Step), Duration>);
+
+impl Baseline {
+    pub fn from_measurements(measurements: &[Measurement]) -> Baseline {
+        Baseline(
+            measurements
+                .iter()
+                .map(|m| ((m.day, m.step), m.stats.median))
+                .collect(),
+        )
+    }
+
+    pub fn load(path: &Path) -> Result<Baseline, FileError> {
+        let mut baseline = Baseline::default();
+        answers::load_entries(path, |day, key, value| baseline.insert(day, key, value))?;
+        Ok(baseline)
+    }
+
+    #[cfg(test)]
+    fn parse(text: &str) -> Result<Baseline, (usize, String)> {
+        let mut baseline = Baseline::default();
+        answers::parse_entries(text, |day, key, value| baseline.insert(day, key, value))?;
+        Ok(baseline)
+    }
+
+    fn insert(&mut self, day: u32, key: &str, value: &str) -> Result<(), String> {
+        let step = parse_step(key)
+            .ok_or_else(|| format!("expected parse or a key like part1, found '{}'", key))?;
+        let nanos = value
+            .parse()
+            .map_err(|_| format!("expected a time in nanoseconds, found '{}'", value))?;
+        if self
+            .0
+            .insert((day, step), Duration::from_nanos(nanos))
+            .is_some()
+        {
+            return Err(format!("duplicate time for day {} {}", day, step));
+        }
+        Ok(())
+    }
+
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        std::fs::write(path, self.to_string())
+    }
+
+    pub fn get(&self, day: u32, step: Step) -> Option<Duration> {
+        self.0.get(&(day, step)).copied()
+    }
+}
+
+impl fmt::Display for Baseline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "# Median times in nanoseconds, written by `aoc-2023 bench`."
+        )?;
+        let mut last_day = None;
+        for (&(day, step), time) in &self.0 {
+            if last_day != Some(day) {
+                writeln!(f, "\n[day{}]", day)?;
+                last_day = Some(day);
+            }
+            writeln!(f, "{} = {}", step, time.as_nanos())?;
+        }
+        Ok(())
+    }
+}
+
+fn micros(time: Duration) -> String {
+    format!("{:.1}", time.as_secs_f64() * 1e6)
+}
+
+/// The change of `time` against `baseline` in percent.
+fn change(time: Duration, baseline: Duration) -> Option<f64> {
+    if baseline.is_zero() {
+        return None;
+    }
+    Some((time.as_secs_f64() / baseline.as_secs_f64() - 1.0) * 100.0)
+}
+
+/// A table of the statistics of every step in microseconds. With a baseline the
+/// median is compared with it, and steps more than `threshold` percent slower are
+/// marked. Returns the table and the number of regressions.
+pub fn table(
+    measurements: &[Measurement],
+    baseline: Option<&Baseline>,
+    threshold: u32,
+) -> (String, usize) {
+    let mut header = format!(
+        "{:>5} | {:>5} | {:>10} | {:>10} | {:>10} | {:>10}",
+        "Day", "Step", "Min us", "Median us", "Mean us", "Stddev us"
+    );
+    if baseline.is_some() {
+        header += &format!(" | {:>11} | {:>7}", "Baseline us", "Change");
+    }
+
+    let mut regressions = 0;
+    let mut lines = vec![header.clone(), "-".repeat(header.len())];
+    for m in measurements {
+        let mut line = format!(
+            "{:>5} | {:>5} | {:>10} | {:>10} | {:>10} | {:>10}",
+            m.day,
+            m.step,
+            micros(m.stats.min),
+            micros(m.stats.median),
+            micros(m.stats.mean),
+            micros(m.stats.stddev)
+        );
+        if let Some(baseline) = baseline {
+            match baseline.get(m.day, m.step) {
+                Some(time) => {
+                    line += &format!(" | {:>11}", micros(time));
+                    match change(m.stats.median, time) {
+                        Some(change) => {
+                            line += &format!(" | {:>+6.1}%", change);
+                            if change > threshold as f64 {
+                                line += " REGRESSION";
+                                regressions += 1;
+                            }
+                        }
+                        None => line += &format!(" | {:>7}", "-"),
+                    }
+                }
+                None => line += &format!(" | {:>11} | {:>7}", "-", "-"),
+            }
+        }
+        lines.push(line);
+    }
+    (lines.join("\n"), regressions)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn us(micros: u64) -> Duration {
+        Duration::from_micros(micros)
+    }
+
+    #[test]
+    fn stats() {
+        let stats = Stats::new(&[us(4), us(2), us(9), us(5)]);
+        assert_eq!(stats.min, us(2));
+        assert_eq!(stats.median, Duration::from_nanos(4500));
+        assert_eq!(stats.mean, Duration::from_nanos(5000));
+        // The sample variance is (1 + 9 + 16 + 0) / 3 us^2.
+        let expected = (26.0f64 / 3.0).sqrt();
+        assert!((stats.stddev.as_secs_f64() * 1e6 - expected).abs() < 1e-3);
+
+        let single = Stats::new(&[us(7)]);
+        assert_eq!(single.median, us(7));
+        assert_eq!(single.stddev, Duration::ZERO);
+    }
+
+    fn measurement(day: u32, step: Step, median: u64) -> Measurement {
+        Measurement {
+            day,
+            step,
+            stats: Stats::new(&[us(median)]),
+        }
+    }
+
+    #[test]
+    fn baseline_round_trip() {
+        let measurements = [
+            measurement(6, Step::Parse, 3),
+            measurement(6, Step::Part(1), 10),
+            measurement(9, Step::Part(2), 250),
+        ];
+        let baseline = Baseline::from_measurements(&measurements);
+        let text = baseline.to_string();
+        assert!(text.contains("[day6]\nparse = 3000\npart1 = 10000\n\n[day9]\npart2 = 250000\n"));
+        assert_eq!(Baseline::parse(&text).unwrap(), baseline);
+
+        assert_eq!(Baseline::parse("[day1]\nsolve = 1").unwrap_err().0, 2);
+        assert_eq!(Baseline::parse("[day1]\nparse = 1.5").unwrap_err().0, 2);
+        assert_eq!(
+            Baseline::parse("[day1]\nparse = 1\nparse = 2").unwrap_err(),
+            (3, "duplicate time for day 1 parse".to_owned())
+        );
+    }
+
+    #[test]
+    fn regressions() {
+        let baseline = Baseline::from_measurements(&[
+            measurement(6, Step::Part(1), 100),
+            measurement(6, Step::Part(2), 100),
+        ]);
+        let measurements = [
+            measurement(6, Step::Part(1), 105),
+            measurement(6, Step::Part(2), 150),
+            measurement(7, Step::Part(1), 1),
+        ];
+        let (text, regressions) = table(&measurements, Some(&baseline), 10);
+        assert_eq!(regressions, 1);
+        let lines: Vec<&str> = text.lines().collect();
+        assert!(lines[2].ends_with("|       100.0 |   +5.0%"));
+        assert!(lines[3].ends_with("|       100.0 |  +50.0% REGRESSION"));
+        assert!(lines[4].ends_with("|           - |       -"));
+
+        let (_, regressions) = table(&measurements, None, 10);
+        assert_eq!(regressions, 0);
+    }
+}
@@ -0,0 +1,653 @@
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+
+use crate::answers::DEFAULT_ANSWERS_FILE;
+use crate::input::{InputOptions, InputSource};
+use crate::solution::Extras;
+use crate::trace::TraceFormat;
+
+pub const USAGE: &str = "\
+Usage: aoc-2023 [COMMAND]
+
+Commands:
+  run <DAYS> [TASK]   Run the selected days. DAYS is a single day (17) or an
+                      inclusive range (10..15). TASK restricts the run to one task.
+  run --all           Run every day (default when no command is given).
+  verify <DAYS> [TASK], verify --all
+                      Run the selected days and compare the results with the
+                      expected answers. Exits with status 1 on any mismatch.
+  bench <DAYS> [TASK], bench --all
+                      Time the selected days over repeated runs, one day after
+                      the other. Exits with status 1 on a regression against the
+                      baseline.
+  help                Print this message.
+
+Run options:
+  --input <PATH>       Read the puzzle input from PATH, or from stdin if PATH is '-'.
+                       Only valid when a single day is selected.
+  --inputs-dir <DIR>   Read the input of day N from DIR/dayN.txt (default: src).
+  --format <FORMAT>    Print the results as a table (default), as JSON or as CSV.
+                       Only valid for run.
+  --jobs <N>           Run up to N days at the same time (default: the number of
+                       CPUs). Results are still printed in day order.
+  --serial             Run one day after the other, for undisturbed timings.
+                       bench always does, so it takes neither option.
+  --dot <DIR>          Write the graph of every selected day whose input is one to
+                       DIR/dayN.dot, in the DOT language of Graphviz. Only valid
+                       for run.
+  --explain <DIR>      Write how the answers of every selected day that can show
+                       it come about to DIR/dayN.txt, such as the route taken.
+                       The part times then include it. Only valid for run.
+  --trace <FILE>       Write every pulse sent during the first presses of the button
+                       to FILE. Only valid for run with a single day.
+  --trace-format <FORMAT>
+                       Write the trace as JSON Lines (jsonl, default) or as a Value
+                       Change Dump for waveform viewers (vcd).
+  --presses <N>        Trace the first N presses (default: 1000).
+
+Verify options:
+  --answers <PATH>     Read the expected answers from PATH
+                       (default: answers.toml in the inputs directory).
+
+Bench options:
+  --warmup <N>         Run every day N times before measuring (default: 3).
+  --runs <N>           Measure N runs of every day (default: 10).
+  --save-baseline <PATH>
+                       Write the median times to PATH.
+  --baseline <PATH>    Compare the median times with the ones saved in PATH.
+  --threshold <PERCENT>
+                       Report a regression when a median is more than PERCENT
+                       percent slower than the baseline (default: 10).";
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Command {
+    Run(RunArgs),
+    Verify(RunArgs),
+    Bench(RunArgs, BenchArgs),
+    Help,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct RunArgs {
+    pub selection: Selection,
+    pub input: InputOptions,
+    pub answers: Option<PathBuf>,
+    pub format: Format,
+    /// The number of days run at the same time, `None` for one per CPU.
+    pub jobs: Option<usize>,
+    /// The directory to write the graphs of the selected days to.
+    pub dot: Option<PathBuf>,
+    /// The directory to write the explanations of the answers to.
+    pub explain: Option<PathBuf>,
+    pub trace: Option<TraceArgs>,
+}
+
+/// Where and how `run` writes the trace of a day.
+#[derive(Debug, PartialEq, Eq)]
+pub struct TraceArgs {
+    pub path: PathBuf,
+    pub format: TraceFormat,
+    pub presses: u64,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct BenchArgs {
+    pub warmup: usize,
+    pub runs: usize,
+    pub save_baseline: Option<PathBuf>,
+    pub baseline: Option<PathBuf>,
+    /// The slowdown against the baseline in percent above which a time counts as
+    /// a regression.
+    pub threshold: u32,
+}
+
+impl Default for BenchArgs {
+    fn default() -> Self {
+        BenchArgs {
+            warmup: 3,
+            runs: 10,
+            save_baseline: None,
+            baseline: None,
+            threshold: 10,
+        }
+    }
+}
+
+/// How `run` prints its results.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// One line per answer followed by a table of timings.
+    #[default]
+    Table,
+    Json,
+    Csv,
+}
+
+impl RunArgs {
+    pub fn answers_path(&self) -> PathBuf {
+        self.answers
+            .clone()
+            .unwrap_or_else(|| self.input.dir.join(DEFAULT_ANSWERS_FILE))
+    }
+
+    /// What the run options ask each day to produce besides the answers.
+    pub fn extras(&self) -> Extras {
+        Extras {
+            graph: self.dot.is_some(),
+            explain: self.explain.is_some(),
+            trace: self.trace.as_ref().map(|trace| trace.presses),
+        }
+    }
+
+    pub fn jobs(&self) -> usize {
+        self.jobs
+            .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |jobs| jobs.get()))
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Selection {
+    pub days: Option<RangeInclusive<u32>>,
+    pub task: Option<u32>,
+}
+
+impl Selection {
+    pub fn all() -> Self {
+        Selection {
+            days: None,
+            task: None,
+        }
+    }
+
+    pub fn matches(&self, day: u32, task: u32) -> bool {
+        self.days.as_ref().is_none_or(|days| days.contains(&day))
+            && self.task.is_none_or(|t| t == task)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct CliError(String);
+
+impl CliError {
+    pub fn new(message: String) -> Self {
+        CliError(message)
+    }
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+pub fn parse_args<I: IntoIterator<Item = String>>(args: I) -> Result<Command, CliError> {
+    let mut args = args.into_iter();
+    match args.next().as_deref() {
+        None => Ok(Command::Run(RunArgs {
+            selection: Selection::all(),
+            input: InputOptions::default(),
+            answers: None,
+            format: Format::default(),
+            jobs: None,
+            dot: None,
+            explain: None,
+            trace: None,
+        })),
+        Some("help" | "--help" | "-h") => Ok(Command::Help),
+        Some("run") => parse_run(args, "run", None).map(Command::Run),
+        Some("verify") => parse_run(args, "verify", None).map(Command::Verify),
+        Some("bench") => {
+            let mut bench = BenchArgs::default();
+            let run = parse_run(args, "bench", Some(&mut bench))?;
+            Ok(Command::Bench(run, bench))
+        }
+        Some(other) => Err(CliError(format!("unknown command '{}'", other))),
+    }
+}
+
+/// Parses the arguments of `command`, which is "run", "verify" or "bench". The
+/// bench options are only accepted when `bench` is given and are stored there.
+fn parse_run<I: Iterator<Item = String>>(
+    mut args: I,
+    command: &str,
+    mut bench: Option<&mut BenchArgs>,
+) -> Result<RunArgs, CliError> {
+    let mut positionals = Vec::new();
+    let mut all = false;
+    let mut input = InputOptions::default();
+    let mut answers = None;
+    let mut format = Format::default();
+    let mut jobs = None;
+    let mut serial = false;
+    let mut dot = None;
+    let mut explain = None;
+    let mut trace = None;
+    let mut trace_format = None;
+    let mut presses = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--all" => all = true,
+            "--input" => {
+                input.source = Some(match expect_value(&mut args, &arg)?.as_str() {
+                    "-" => InputSource::Stdin,
+                    path => InputSource::File(PathBuf::from(path)),
+                })
+            }
+            "--inputs-dir" => input.dir = PathBuf::from(expect_value(&mut args, &arg)?),
+            "--answers" if command == "verify" => {
+                answers = Some(PathBuf::from(expect_value(&mut args, &arg)?))
+            }
+            "--format" if command == "run" => {
+                format = parse_format(&expect_value(&mut args, &arg)?)?
+            }
+            "--jobs" if bench.is_none() => {
+                let value = expect_value(&mut args, &arg)?;
+                match parse_number(&value, "number of jobs")? {
+                    0 => return Err(CliError("--jobs expects at least 1".to_owned())),
+                    n => jobs = Some(n as usize),
+                }
+            }
+            "--serial" if bench.is_none() => serial = true,
+            "--dot" if command == "run" => {
+                dot = Some(PathBuf::from(expect_value(&mut args, &arg)?))
+            }
+            "--explain" if command == "run" => {
+                explain = Some(PathBuf::from(expect_value(&mut args, &arg)?))
+            }
+            "--trace" if command == "run" => {
+                trace = Some(PathBuf::from(expect_value(&mut args, &arg)?))
+            }
+            "--trace-format" if command == "run" => {
+                trace_format = Some(parse_trace_format(&expect_value(&mut args, &arg)?)?)
+            }
+            "--presses" if command == "run" => {
+                let value = expect_value(&mut args, &arg)?;
+                presses = Some(u64::from(parse_number(&value, "number of presses")?))
+            }
+            "--warmup" | "--runs" | "--save-baseline" | "--baseline" | "--threshold"
+                if bench.is_some() =>
+            {
+                let bench = bench.as_deref_mut().unwrap();
+                let value = expect_value(&mut args, &arg)?;
+                match arg.as_str() {
+                    "--warmup" => bench.warmup = parse_number(&value, "number of runs")? as usize,
+                    "--runs" => match parse_number(&value, "number of runs")? {
+                        0 => return Err(CliError("--runs expects at least 1".to_owned())),
+                        n => bench.runs = n as usize,
+                    },
+                    "--save-baseline" => bench.save_baseline = Some(PathBuf::from(value)),
+                    "--baseline" => bench.baseline = Some(PathBuf::from(value)),
+                    _ => bench.threshold = parse_number(&value, "threshold")?,
+                }
+            }
+            flag if flag.starts_with("--") => {
+                return Err(CliError(format!("unknown option '{}'", flag)))
+            }
+            _ => positionals.push(arg),
+        }
+    }
+
+    let selection = match (all, positionals.as_slice()) {
+        (true, []) => Selection::all(),
+        (true, [extra, ..]) => {
+            return Err(CliError(format!("unexpected argument '{}'", extra)));
+        }
+        (false, []) => {
+            return Err(CliError(format!(
+                "{} expects a day, a range of days or --all",
+                command
+            )));
+        }
+        (false, [days]) => Selection {
+            days: Some(parse_days(days)?),
+            task: None,
+        },
+        (false, [days, task]) => Selection {
+            days: Some(parse_days(days)?),
+            task: Some(parse_number(task, "task")?),
+        },
+        (false, [_, _, extra, ..]) => {
+            return Err(CliError(format!("unexpected argument '{}'", extra)));
+        }
+    };
+
+    let single_day = matches!(&selection.days, Some(days) if days.start() == days.end());
+    if input.source.is_some() && !single_day {
+        return Err(CliError("--input requires a single day".to_owned()));
+    }
+
+    let trace = match trace {
+        Some(_) if !single_day => {
+            return Err(CliError("--trace requires a single day".to_owned()));
+        }
+        Some(path) => Some(TraceArgs {
+            path,
+            format: trace_format.unwrap_or_default(),
+            presses: presses.unwrap_or(1000),
+        }),
+        None if trace_format.is_some() => {
+            return Err(CliError("--trace-format requires --trace".to_owned()));
+        }
+        None if presses.is_some() => {
+            return Err(CliError("--presses requires --trace".to_owned()));
+        }
+        None => None,
+    };
+
+    if serial {
+        if jobs.is_some_and(|jobs| jobs > 1) {
+            return Err(CliError("--serial conflicts with --jobs".to_owned()));
+        }
+        jobs = Some(1);
+    }
+
+    Ok(RunArgs {
+        selection,
+        input,
+        answers,
+        format,
+        jobs,
+        dot,
+        explain,
+        trace,
+    })
+}
+
+fn expect_value<I: Iterator<Item = String>>(args: &mut I, flag: &str) -> Result<String, CliError> {
+    args.next()
+        .ok_or_else(|| CliError(format!("{} expects a value", flag)))
+}
+
+fn parse_days(s: &str) -> Result<RangeInclusive<u32>, CliError> {
+    if let Some((from, to)) = s.split_once("..") {
+        let to = to.strip_prefix('=').unwrap_or(to);
+        let from = parse_number(from, "day")?;
+        let to = parse_number(to, "day")?;
+        if from > to {
+            return Err(CliError(format!("empty day range '{}'", s)));
+        }
+        Ok(from..=to)
+    } else {
+        let day = parse_number(s, "day")?;
+        Ok(day..=day)
+    }
+}
+
+fn parse_format(s: &str) -> Result<Format, CliError> {
+    match s {
+        "table" => Ok(Format::Table),
+        "json" => Ok(Format::Json),
+        "csv" => Ok(Format::Csv),
+        _ => Err(CliError(format!(
+            "unknown format '{}' (expected table, json or csv)",
+            s
+        ))),
+    }
+}
+
+fn parse_trace_format(s: &str) -> Result<TraceFormat, CliError> {
+    match s {
+        "jsonl" => Ok(TraceFormat::JsonLines),
+        "vcd" => Ok(TraceFormat::Vcd),
+        _ => Err(CliError(format!(
+            "unknown trace format '{}' (expected jsonl or vcd)",
+            s
+        ))),
+    }
+}
+
+fn parse_number(s: &str, what: &str) -> Result<u32, CliError> {
+    s.parse::<u32>()
+        .map_err(|_| CliError(format!("invalid {} '{}'", what, s)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Command, CliError> {
+        parse_args(args.iter().map(|s| s.to_string()))
+    }
+
+    fn selection(args: &[&str]) -> Selection {
+        match parse(args) {
+            Ok(Command::Run(run)) => run.selection,
+            x => panic!("{:?}", x),
+        }
+    }
+
+    #[test]
+    fn parse_run_selections() {
+        assert_eq!(selection(&[]), Selection::all());
+        assert_eq!(selection(&["run", "--all"]), Selection::all());
+        assert_eq!(
+            selection(&["run", "17", "2"]),
+            Selection {
+                days: Some(17..=17),
+                task: Some(2),
+            }
+        );
+        assert_eq!(
+            selection(&["run", "10..15"]),
+            Selection {
+                days: Some(10..=15),
+                task: None,
+            }
+        );
+        assert_eq!(
+            selection(&["run", "10..=15"]),
+            Selection {
+                days: Some(10..=15),
+                task: None,
+            }
+        );
+    }
+
+    #[test]
+    fn parse_input_options() {
+        let Ok(Command::Run(run)) = parse(&["run", "17", "--input", "-"]) else {
+            panic!();
+        };
+        assert_eq!(run.input.source, Some(InputSource::Stdin));
+
+        let Ok(Command::Run(run)) = parse(&["run", "--inputs-dir", "inputs", "--all"]) else {
+            panic!();
+        };
+        assert_eq!(run.input.source, None);
+        assert_eq!(run.input.dir, PathBuf::from("inputs"));
+
+        assert!(parse(&["run", "1..3", "--input", "day1.txt"]).is_err());
+        assert!(parse(&["run", "1", "--input"]).is_err());
+    }
+
+    #[test]
+    fn parse_verify() {
+        let Ok(Command::Verify(run)) = parse(&["verify", "--all", "--inputs-dir", "inputs"]) else {
+            panic!();
+        };
+        assert_eq!(run.selection, Selection::all());
+        assert_eq!(run.answers_path(), PathBuf::from("inputs/answers.toml"));
+
+        let Ok(Command::Verify(run)) = parse(&["verify", "3", "--answers", "expected.toml"]) else {
+            panic!();
+        };
+        assert_eq!(run.answers_path(), PathBuf::from("expected.toml"));
+
+        assert!(parse(&["run", "3", "--answers", "expected.toml"]).is_err());
+        assert!(parse(&["verify"]).is_err());
+    }
+
+    #[test]
+    fn parse_format_option() {
+        let Ok(Command::Run(run)) = parse(&["run", "--all"]) else {
+            panic!();
+        };
+        assert_eq!(run.format, Format::Table);
+
+        let Ok(Command::Run(run)) = parse(&["run", "1..3", "--format", "json"]) else {
+            panic!();
+        };
+        assert_eq!(run.format, Format::Json);
+
+        let Ok(Command::Run(run)) = parse(&["run", "--format", "csv", "--all"]) else {
+            panic!();
+        };
+        assert_eq!(run.format, Format::Csv);
+
+        assert!(parse(&["run", "--all", "--format", "xml"]).is_err());
+        assert!(parse(&["run", "--all", "--format"]).is_err());
+        assert!(parse(&["verify", "--all", "--format", "json"]).is_err());
+    }
+
+    #[test]
+    fn parse_jobs() {
+        let jobs = |args: &[&str]| match parse(args) {
+            Ok(Command::Run(run) | Command::Verify(run)) => run.jobs,
+            x => panic!("{:?}", x),
+        };
+        assert_eq!(jobs(&["run", "--all"]), None);
+        assert_eq!(jobs(&["run", "--all", "--jobs", "4"]), Some(4));
+        assert_eq!(jobs(&["verify", "--all", "--serial"]), Some(1));
+        assert_eq!(jobs(&["run", "--serial", "--jobs", "1", "--all"]), Some(1));
+
+        assert!(parse(&["run", "--all", "--jobs", "0"]).is_err());
+        assert!(parse(&["run", "--all", "--jobs", "x"]).is_err());
+        assert!(parse(&["run", "--all", "--jobs", "2", "--serial"]).is_err());
+    }
+
+    #[test]
+    fn parse_dot() {
+        let Ok(Command::Run(run)) = parse(&["run", "20..25", "--dot", "graphs"]) else {
+            panic!();
+        };
+        assert_eq!(run.dot, Some(PathBuf::from("graphs")));
+
+        let Ok(Command::Run(run)) = parse(&["run", "--all"]) else {
+            panic!();
+        };
+        assert_eq!(run.dot, None);
+
+        assert!(parse(&["run", "--all", "--dot"]).is_err());
+        assert!(parse(&["verify", "--all", "--dot", "graphs"]).is_err());
+    }
+
+    #[test]
+    fn parse_explain() {
+        let Ok(Command::Run(run)) = parse(&["run", "17", "--explain", "routes"]) else {
+            panic!();
+        };
+        assert_eq!(run.explain, Some(PathBuf::from("routes")));
+        assert_eq!(run.dot, None);
+
+        assert!(parse(&["run", "17", "--explain"]).is_err());
+        assert!(parse(&["bench", "17", "--explain", "routes"]).is_err());
+    }
+
+    #[test]
+    fn parse_trace() {
+        let Ok(Command::Run(run)) = parse(&["run", "20", "1", "--trace", "pulses.jsonl"]) else {
+            panic!();
+        };
+        assert_eq!(
+            run.trace,
+            Some(TraceArgs {
+                path: PathBuf::from("pulses.jsonl"),
+                format: TraceFormat::JsonLines,
+                presses: 1000,
+            })
+        );
+
+        let Ok(Command::Run(run)) = parse(&[
+            "run",
+            "20",
+            "--presses",
+            "4",
+            "--trace",
+            "pulses.vcd",
+            "--trace-format",
+            "vcd",
+        ]) else {
+            panic!();
+        };
+        assert_eq!(
+            run.trace,
+            Some(TraceArgs {
+                path: PathBuf::from("pulses.vcd"),
+                format: TraceFormat::Vcd,
+                presses: 4,
+            })
+        );
+
+        assert!(parse(&["run", "20..25", "--trace", "pulses.jsonl"]).is_err());
+        assert!(parse(&["run", "20", "--trace", "t", "--trace-format", "csv"]).is_err());
+        assert!(parse(&["run", "20", "--trace-format", "vcd"]).is_err());
+        assert!(parse(&["run", "20", "--presses", "4"]).is_err());
+        assert!(parse(&["verify", "20", "--trace", "pulses.jsonl"]).is_err());
+    }
+
+    #[test]
+    fn parse_bench() {
+        let Ok(Command::Bench(run, bench)) = parse(&["bench", "6"]) else {
+            panic!();
+        };
+        assert_eq!(run.selection.days, Some(6..=6));
+        assert_eq!(bench, BenchArgs::default());
+
+        let args = [
+            "bench",
+            "--all",
+            "--warmup",
+            "0",
+            "--runs",
+            "50",
+            "--baseline",
+            "old.toml",
+            "--save-baseline",
+            "new.toml",
+            "--threshold",
+            "25",
+        ];
+        let Ok(Command::Bench(_, bench)) = parse(&args) else {
+            panic!();
+        };
+        assert_eq!(
+            bench,
+            BenchArgs {
+                warmup: 0,
+                runs: 50,
+                save_baseline: Some(PathBuf::from("new.toml")),
+                baseline: Some(PathBuf::from("old.toml")),
+                threshold: 25,
+            }
+        );
+
+        assert!(parse(&["bench", "6", "--runs", "0"]).is_err());
+        assert!(parse(&["bench", "6", "--jobs", "2"]).is_err());
+        assert!(parse(&["run", "6", "--runs", "2"]).is_err());
+        assert!(parse(&["bench"]).is_err());
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(parse(&["run"]).is_err());
+        assert!(parse(&["run", "x"]).is_err());
+        assert!(parse(&["run", "15..10"]).is_err());
+        assert!(parse(&["run", "1", "2", "3"]).is_err());
+        assert!(parse(&["run", "--all", "3"]).is_err());
+        assert!(parse(&["run", "3", "--verbose"]).is_err());
+        assert!(parse(&["walk"]).is_err());
+    }
+
+    #[test]
+    fn selection_matches() {
+        let selection = Selection {
+            days: Some(3..=5),
+            task: Some(1),
+        };
+        assert!(selection.matches(3, 1));
+        assert!(selection.matches(5, 1));
+        assert!(!selection.matches(5, 2));
+        assert!(!selection.matches(6, 1));
+    }
+}
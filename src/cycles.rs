@@ -0,0 +1,180 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use itertools::Itertools;
+
+/// The steps of a deterministic process at which something of interest happens, a
+/// hit. From step `offset` on the process repeats every `length` steps.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Cycle {
+    pub offset: u64,
+    pub length: u64,
+    /// The hits before `offset`.
+    pub prefix_hits: Vec<u64>,
+    /// The hits in `offset..offset + length`.
+    pub hits: Vec<u64>,
+}
+
+impl Cycle {
+    pub fn is_hit_at(&self, step: u64) -> bool {
+        if step < self.offset {
+            return self.prefix_hits.contains(&step);
+        }
+        let step = self.offset + (step - self.offset) % self.length;
+        self.hits.contains(&step)
+    }
+}
+
+/// Runs a process until it gets back into a state it was in before. `step` returns
+/// the current state and whether the step it then takes is a hit.
+pub fn find_cycle<S: Hash + Eq>(step: impl FnMut() -> (S, bool)) -> Cycle {
+    find_cycle_within(u64::MAX, step).expect("a process with a finite number of states repeats")
+}
+
+/// Like [`find_cycle`], but gives up after `max_steps` steps without a repeated
+/// state.
+pub fn find_cycle_within<S: Hash + Eq>(
+    max_steps: u64,
+    mut step: impl FnMut() -> (S, bool),
+) -> Option<Cycle> {
+    let mut seen: HashMap<S, u64> = HashMap::new();
+    let mut hits = Vec::new();
+    for index in 0..max_steps {
+        let (state, hit) = step();
+        if let Some(&offset) = seen.get(&state) {
+            let (prefix_hits, hits) = hits.iter().partition(|&&hit| hit < offset);
+            return Some(Cycle {
+                offset,
+                length: index - offset,
+                prefix_hits,
+                hits,
+            });
+        }
+        seen.insert(state, index);
+        if hit {
+            hits.push(index);
+        }
+    }
+    None
+}
+
+fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    if b == 0 {
+        (a, 1, 0)
+    } else {
+        let (g, x, y) = extended_gcd(b, a % b);
+        (g, y, x - (a / b) * y)
+    }
+}
+
+/// Combines `x = r1 mod m1` and `x = r2 mod m2` into a single congruence modulo
+/// the least common multiple, if both can hold at once. The moduli do not need to
+/// be coprime.
+fn combine((r1, m1): (i128, i128), (r2, m2): (i128, i128)) -> Option<(i128, i128)> {
+    let (g, p, _) = extended_gcd(m1, m2);
+    if (r2 - r1) % g != 0 {
+        return None;
+    }
+    let lcm = m1 / g * m2;
+    let k = ((r2 - r1) / g * p).rem_euclid(m2 / g);
+    Some(((r1 + m1 * k).rem_euclid(lcm), lcm))
+}
+
+/// The first step that is a hit in all cycles at the same time.
+pub fn first_common_hit(cycles: &[Cycle]) -> Option<u64> {
+    // Before the last process enters its cycle, only prefix hits are candidates.
+    let mut candidates = cycles
+        .iter()
+        .flat_map(|cycle| cycle.prefix_hits.iter().copied())
+        .collect_vec();
+    candidates.sort();
+    if let Some(&step) = candidates
+        .iter()
+        .find(|&&step| cycles.iter().all(|cycle| cycle.is_hit_at(step)))
+    {
+        return Some(step);
+    }
+
+    // Afterwards every process is in its cycle, so one hit of each has to line up.
+    let start = cycles.iter().map(|cycle| cycle.offset).max()?;
+    let mut congruences = vec![(0i128, 1i128)];
+    for cycle in cycles {
+        let length = cycle.length as i128;
+        congruences = congruences
+            .iter()
+            .cartesian_product(&cycle.hits)
+            .filter_map(|(&congruence, &hit)| combine(congruence, (hit as i128, length)))
+            .unique()
+            .collect();
+    }
+    congruences
+        .into_iter()
+        .map(|(r, m)| {
+            let start = start as i128;
+            (start + (r - start).rem_euclid(m)) as u64
+        })
+        .min()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn cycle_of_a_sequence() {
+        // 0, 1, 2, 3, 4, 2, 3, 4, ... with hits at the even numbers.
+        let mut value = 0;
+        let cycle = find_cycle(|| {
+            let current = value;
+            value = if value == 4 { 2 } else { value + 1 };
+            (current, current % 2 == 0)
+        });
+        assert_eq!(
+            cycle,
+            Cycle {
+                offset: 2,
+                length: 3,
+                prefix_hits: vec![0],
+                hits: vec![2, 4],
+            }
+        );
+        assert!(cycle.is_hit_at(0));
+        assert!(!cycle.is_hit_at(1));
+        assert!(cycle.is_hit_at(7));
+        assert!(!cycle.is_hit_at(9));
+
+        // 1, 2, 3, 4, 5, 5, ...
+        let mut value = 0;
+        let mut step = || {
+            value += 1;
+            (value.min(5), false)
+        };
+        assert_eq!(find_cycle_within(4, &mut step), None);
+        assert_eq!(find_cycle_within(4, &mut step).map(|c| c.length), Some(1));
+    }
+
+    #[test]
+    fn congruences() {
+        assert_eq!(combine((2, 3), (3, 5)), Some((8, 15)));
+        assert_eq!(combine((1, 4), (3, 6)), Some((9, 12)));
+        assert_eq!(combine((0, 4), (1, 6)), None);
+    }
+
+    #[test]
+    fn common_hits() {
+        let cycle = |offset, length, prefix_hits: &[u64], hits: &[u64]| Cycle {
+            offset,
+            length,
+            prefix_hits: prefix_hits.to_vec(),
+            hits: hits.to_vec(),
+        };
+        // Plain loops that hit on their last step.
+        let loops = [cycle(0, 3, &[], &[2]), cycle(0, 4, &[], &[3])];
+        assert_eq!(first_common_hit(&loops), Some(11));
+        // A hit before one of the cycles starts.
+        let early = [cycle(5, 2, &[1], &[5]), cycle(0, 1, &[], &[0])];
+        assert_eq!(first_common_hit(&early), Some(1));
+        let never = [cycle(0, 2, &[], &[0]), cycle(0, 2, &[], &[1])];
+        assert_eq!(first_common_hit(&never), None);
+    }
+}
@@ -1,12 +1,6 @@
-use std::fs::File;
-use std::io::{self, BufRead};
-use std::path::Path;
-
-fn lines_from_file(filename: impl AsRef<Path>) -> io::Result<impl Iterator<Item = String>> {
-    let file = File::open(&filename)?;
-    let reader = io::BufReader::new(file);
-    Ok(reader.lines().map(|l| l.expect("Could not parse line")))
-}
+use crate::error::Error;
+use crate::input::InputSource;
+use crate::solution::{Answer, Solution};
 
 const DIGITS: [(&str, i32); 10] = [
     ("0", 0),
@@ -77,31 +71,59 @@ fn replacer<'a, T>(input: &'a str, replacements: &'a [(&'a str, T)]) -> InOrderR
     }
 }
 
-fn calculate_value(replacements: &[(&str, i32)]) -> i32 {
-    let c = lines_from_file("src/day1_task1.txt")
-        .unwrap()
+fn calculate_value(lines: &[String], replacements: &[(&str, i32)]) -> i32 {
+    lines
+        .iter()
         .map(|line| {
-            replacer(&line, replacements).fold(None, |acc, val| {
+            replacer(line, replacements).fold(None, |acc, val| {
                 acc.or(Some((val, val))).map(|v| (v.0, val))
             })
         })
         .map(|p| p.map(|(l, r)| l * 10 + r).unwrap_or(0))
-        .fold(0, i32::wrapping_add);
-    c
+        .fold(0, i32::wrapping_add)
 }
 
-pub fn task1() -> crate::AOCResult<i32> {
-    crate::AOCResult {
-        day: 1,
-        task: 1,
-        r: calculate_value(&DIGITS),
+pub struct Day1;
+
+impl Solution for Day1 {
+    const DAY: u32 = 1;
+
+    type Input = Vec<String>;
+
+    fn parse(input: &InputSource) -> Result<Self::Input, Error> {
+        Ok(input.lines()?)
+    }
+
+    fn part1(input: &Self::Input) -> Result<Answer, Error> {
+        Ok(Answer::from(calculate_value(input, &DIGITS)))
+    }
+
+    fn part2(input: &Self::Input) -> Result<Answer, Error> {
+        Ok(Answer::from(calculate_value(
+            input,
+            &DIGITS_INCLUDING_WRITTEN_OUT_DIGITS,
+        )))
     }
 }
 
-pub fn task2() -> crate::AOCResult<i32> {
-    crate::AOCResult {
-        day: 1,
-        task: 2,
-        r: calculate_value(&DIGITS_INCLUDING_WRITTEN_OUT_DIGITS),
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::solution::example;
+
+    #[test]
+    fn example_part1() {
+        assert_eq!(
+            example::<Day1>(include_str!("examples/day1.txt"), 1),
+            Answer(142)
+        );
+    }
+
+    #[test]
+    fn example_part2() {
+        assert_eq!(
+            example::<Day1>(include_str!("examples/day1_part2.txt"), 2),
+            Answer(281)
+        );
     }
 }
@@ -1,15 +1,14 @@
-use std::fs::File;
-use std::io::{self, BufRead};
-use std::path::Path;
+use crate::error::Error;
+use crate::geometry::{Direction, Pos};
+use crate::grid::Grid;
+use crate::input::InputSource;
+use crate::parse::ParseError;
+use crate::solution::{Answer, Solution};
 
-fn lines_from_file(filename: impl AsRef<Path>) -> io::Result<impl Iterator<Item = String>> {
-    let file = File::open(&filename)?;
-    let reader = io::BufReader::new(file);
-    Ok(reader.lines().map(|l| l.expect("Could not parse line")))
-}
+const DAY: u32 = 10;
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
-enum Field {
+pub enum Field {
     NE,
     NS,
     NW,
@@ -20,250 +19,110 @@ enum Field {
     None,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Direction {
-    North,
-    East,
-    South,
-    West,
-    None,
-}
-
-#[derive(Debug, Clone, PartialEq, Eq, Copy)]
-struct State {
-    line: usize,
-    col: usize,
-    direction: Direction,
+impl Field {
+    /// The two sides a pipe connects, `None` for the start and empty tiles.
+    fn connections(self) -> Option<[Direction; 2]> {
+        use Direction::*;
+        match self {
+            Field::NE => Some([North, East]),
+            Field::NS => Some([North, South]),
+            Field::NW => Some([North, West]),
+            Field::ES => Some([East, South]),
+            Field::EW => Some([East, West]),
+            Field::SW => Some([South, West]),
+            Field::Start | Field::None => Option::None,
+        }
+    }
 }
 
-impl State {
-    fn next(self, field: Field) -> State {
-        match (field, self.direction) {
-            (Field::NE, Direction::North) => Self {
-                line: self.line,
-                col: self.col + 1,
-                direction: Direction::West,
-            },
-            (Field::NE, Direction::East) => Self {
-                line: self.line - 1,
-                col: self.col,
-                direction: Direction::South,
-            },
-            (Field::NS, Direction::North) => Self {
-                line: self.line + 1,
-                col: self.col,
-                direction: Direction::North,
-            },
-            (Field::NS, Direction::South) => Self {
-                line: self.line - 1,
-                col: self.col,
-                direction: Direction::South,
-            },
-            (Field::NW, Direction::North) => Self {
-                line: self.line,
-                col: self.col - 1,
-                direction: Direction::East,
-            },
-            (Field::NW, Direction::West) => Self {
-                line: self.line - 1,
-                col: self.col,
-                direction: Direction::South,
-            },
-            (Field::ES, Direction::East) => Self {
-                line: self.line + 1,
-                col: self.col,
-                direction: Direction::North,
-            },
-            (Field::ES, Direction::South) => Self {
-                line: self.line,
-                col: self.col + 1,
-                direction: Direction::West,
-            },
-            (Field::EW, Direction::East) => Self {
-                line: self.line,
-                col: self.col - 1,
-                direction: Direction::East,
-            },
-            (Field::EW, Direction::West) => Self {
-                line: self.line,
-                col: self.col + 1,
-                direction: Direction::West,
-            },
-            (Field::SW, Direction::South) => Self {
-                line: self.line,
-                col: self.col - 1,
-                direction: Direction::East,
-            },
-            (Field::SW, Direction::West) => Self {
-                line: self.line + 1,
-                col: self.col,
-                direction: Direction::North,
-            },
-            (Field::Start, _) => Self {
-                line: self.line,
-                col: self.col,
-                direction: Direction::None,
-            },
-            (Field::None, _) => Self {
-                line: self.line,
-                col: self.col,
-                direction: Direction::None,
-            },
-            _ => Self {
-                line: self.line,
-                col: self.col,
-                direction: Direction::None,
-            },
+/// Follows the pipes from `start`, leaving it towards `heading`. Returns the tiles
+/// of the loop in walking order, starting with `start`, or `None` if the pipes
+/// lead anywhere else.
+fn walk_loop(grid: &Grid<Field>, start: Pos, mut heading: Direction) -> Option<Vec<Pos>> {
+    // Every pipe has two ends and we never turn back, so the walk cannot enter a
+    // cycle that does not pass through the start.
+    let mut tiles = vec![start];
+    let mut pos = start;
+    loop {
+        pos = pos.step_within(heading, grid.width(), grid.height())?;
+        let field = grid[pos.into()];
+        if field == Field::Start {
+            return Some(tiles);
         }
+        let [a, b] = field.connections()?;
+        heading = match heading.reverse() {
+            came_from if came_from == a => b,
+            came_from if came_from == b => a,
+            _ => return None,
+        };
+        tiles.push(pos);
     }
 }
 
-fn parse_line(line: &str) -> Vec<Field> {
+fn find_loop(grid: &Grid<Field>) -> Result<Vec<Pos>, Error> {
+    let start = find_start(grid)?;
+    Direction::ALL
+        .into_iter()
+        .find_map(|heading| walk_loop(grid, start.into(), heading))
+        .ok_or_else(|| Error::no_solution(DAY, "loop through the start tile"))
+}
+
+fn parse_field(c: char) -> Option<Field> {
     use Field::*;
-    line.chars()
-        .map(|c| match c {
-            'L' => NE,
-            '|' => NS,
-            'J' => NW,
-            'F' => ES,
-            '-' => EW,
-            '7' => SW,
-            'S' => Start,
-            _ => None,
-        })
-        .collect()
+    match c {
+        'L' => Some(NE),
+        '|' => Some(NS),
+        'J' => Some(NW),
+        'F' => Some(ES),
+        '-' => Some(EW),
+        '7' => Some(SW),
+        'S' => Some(Start),
+        '.' => Some(None),
+        _ => Option::None,
+    }
 }
 
-fn parse() -> Vec<Vec<Field>> {
-    lines_from_file("src/day10.txt")
-        .unwrap()
-        .map(|l| parse_line(&l))
-        .collect()
+fn find_start(grid: &Grid<Field>) -> Result<(usize, usize), ParseError> {
+    grid.find(|&field| field == Field::Start)
+        .ok_or_else(|| ParseError::missing("a start tile 'S'").in_day(DAY))
 }
 
-pub fn task1() -> crate::AOCResult<usize> {
-    let grid = parse();
+pub struct Day10;
 
-    let start_pos = grid
-        .iter()
-        .enumerate()
-        .map(|(line, vec)| {
-            vec.iter()
-                .enumerate()
-                .find(|(_, &connection)| connection == Field::Start)
-                .map(|x| (line, x.0))
-        })
-        .find(Option::is_some)
-        .flatten()
-        .unwrap();
+impl Solution for Day10 {
+    const DAY: u32 = DAY;
 
-    let num_steps = find_start_or_loop_or_empty(
-        &grid,
-        State {
-            line: start_pos.0 - 1,
-            col: start_pos.1,
-            direction: Direction::South,
-        },
-    )
-    .or_else(|| {
-        find_start_or_loop_or_empty(
-            &grid,
-            State {
-                line: start_pos.0,
-                col: start_pos.1 + 1,
-                direction: Direction::West,
-            },
-        )
-    })
-    .or_else(|| {
-        find_start_or_loop_or_empty(
-            &grid,
-            State {
-                line: start_pos.0 + 1,
-                col: start_pos.1,
-                direction: Direction::North,
-            },
-        )
-    })
-    .or_else(|| {
-        find_start_or_loop_or_empty(
-            &grid,
-            State {
-                line: start_pos.0,
-                col: start_pos.1 - 1,
-                direction: Direction::East,
-            },
-        )
-    })
-    .unwrap();
+    type Input = Grid<Field>;
 
-    crate::AOCResult {
-        day: 10,
-        task: 1,
-        r: num_steps / 2,
+    fn parse(input: &InputSource) -> Result<Self::Input, Error> {
+        Ok(Grid::parse(
+            DAY,
+            1,
+            &input.grid()?,
+            parse_field,
+            "a pipe, '.' or 'S'",
+        )?)
     }
-}
-
-fn find_start_or_loop_or_empty(grid: &[Vec<Field>], start_state: State) -> Option<usize> {
-    let mut one_step = start_state;
-    let mut two_step = start_state;
-    two_step = two_step.next(grid[two_step.line][two_step.col]);
-
-    let mut count_steps = 1;
 
-    loop {
-        one_step = one_step.next(grid[one_step.line][one_step.col]);
-        two_step = two_step.next(grid[two_step.line][two_step.col]);
-        two_step = two_step.next(grid[two_step.line][two_step.col]);
-        let field = grid[one_step.line][one_step.col];
-        count_steps += 1;
-        if field == Field::Start {
-            return Some(count_steps);
-        }
-        if field == Field::None {
-            return None;
-        }
-        if one_step == two_step {
-            return None;
-        }
+    fn part1(grid: &Self::Input) -> Result<Answer, Error> {
+        let tiles = find_loop(grid)?;
+        Ok(Answer::from(tiles.len() / 2))
     }
-}
 
-pub fn task2() -> crate::AOCResult<i32> {
-    let grid = parse();
+    fn part2(grid: &Self::Input) -> Result<Answer, Error> {
+        let tiles = find_loop(grid)?;
 
-    let start_pos = grid
-        .iter()
-        .enumerate()
-        .map(|(line, vec)| {
-            vec.iter()
-                .enumerate()
-                .find(|(_, &connection)| connection == Field::Start)
-                .map(|x| (line, x.0))
-        })
-        .find(Option::is_some)
-        .flatten()
-        .unwrap();
+        let mut enclosure_grid = create_initial_enclosure_map(grid, &tiles);
+        calculate_outside_fields(&mut enclosure_grid);
 
-    // Already know which one is the correct direction.
-    let mut enclosure_grid = create_initial_enclosure_map(&grid, start_pos);
-    calculate_outside_fields(&mut enclosure_grid);
+        let count = grid
+            .positions()
+            .filter(|&(line, col)| {
+                enclosure_grid[(line * 2 + 1, col * 2 + 1)] == Enclosure::Unknown
+            })
+            .count();
 
-    let mut count = 0;
-    for line in 0..grid.len() {
-        for col in 0..grid.len() {
-            count += if enclosure_grid[line * 2 + 1][col * 2 + 1] == Enclosure::Unknown {
-                1
-            } else {
-                0
-            }
-        }
-    }
-
-    crate::AOCResult {
-        day: 10,
-        task: 2,
-        r: count,
+        Ok(Answer::from(count))
     }
 }
 
@@ -289,62 +148,53 @@ impl From<Field> for Enclosure {
     }
 }
 
-fn create_initial_enclosure_map(
-    grid: &[Vec<Field>],
-    start_pos: (usize, usize),
-) -> Vec<Vec<Enclosure>> {
-    let mut one_step = State {
-        line: start_pos.0 + 1,
-        col: start_pos.1,
-        direction: Direction::North,
-    };
-
-    let mut enclosure = Vec::with_capacity(grid.len() * 2 + 3);
-    for _ in 0..grid.len() * 2 + 3 {
-        enclosure.push(vec![Enclosure::Unknown; grid.len() * 2 + 3]);
+fn create_initial_enclosure_map(grid: &Grid<Field>, tiles: &[Pos]) -> Grid<Enclosure> {
+    // Every tile and every gap between two tiles gets a cell, with a free border
+    // around it all.
+    let mut enclosure = Grid::filled(
+        grid.width() * 2 + 3,
+        grid.height() * 2 + 3,
+        Enclosure::Unknown,
+    );
+
+    let next_tiles = tiles.iter().cycle().skip(1);
+    for (now, next) in tiles.iter().zip(next_tiles) {
+        enclosure[(now.row * 2 + 1, now.col * 2 + 1)] = Enclosure::Wall;
+        enclosure[(now.row + next.row + 1, now.col + next.col + 1)] = Enclosure::Wall;
     }
+    enclosure
+}
 
-    enclosure[start_pos.0 * 2 + 1][start_pos.1 * 2 + 1] = Enclosure::Wall;
-    enclosure[start_pos.0 * 2 + 2][start_pos.1 * 2 + 1] = Enclosure::Wall;
-
-    loop {
-        enclosure[one_step.line * 2 + 1][one_step.col * 2 + 1] = Enclosure::Wall;
-
-        let now = one_step;
-        one_step = one_step.next(grid[one_step.line][one_step.col]);
-        enclosure[now.line + one_step.line + 1][now.col + one_step.col + 1] = Enclosure::Wall;
-
-        let field = grid[one_step.line][one_step.col];
+fn calculate_outside_fields(enclosure: &mut Grid<Enclosure>) {
+    let mut worklist = vec![(0, 0)];
 
-        if field == Field::Start {
-            return enclosure;
+    while let Some(pos) = worklist.pop() {
+        if enclosure[pos] != Enclosure::Unknown {
+            continue;
         }
+        enclosure[pos] = Enclosure::Outside;
+        worklist.extend(enclosure.neighbours4(pos));
     }
 }
 
-fn calculate_outside_fields(enclosure: &mut [Vec<Enclosure>]) {
-    let mut worklist = Vec::new();
-    worklist.push((0, 0));
-
-    // Is Square
-    let length = enclosure.len();
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::solution::example;
+
+    #[test]
+    fn example_part1() {
+        assert_eq!(
+            example::<Day10>(include_str!("examples/day10.txt"), 1),
+            Answer(8)
+        );
+    }
 
-    while let Some((line, col)) = worklist.pop() {
-        if enclosure[line][col] != Enclosure::Unknown {
-            continue;
-        }
-        enclosure[line][col] = Enclosure::Outside;
-        if line > 0 {
-            worklist.push((line - 1, col));
-        }
-        if line < length - 1 {
-            worklist.push((line + 1, col));
-        }
-        if col > 0 {
-            worklist.push((line, col - 1));
-        }
-        if col < length - 1 {
-            worklist.push((line, col + 1));
-        }
+    #[test]
+    fn example_part2() {
+        assert_eq!(
+            example::<Day10>(include_str!("examples/day10_part2.txt"), 2),
+            Answer(4)
+        );
     }
 }
@@ -1,131 +1,74 @@
-use std::fs::File;
-use std::io::{self, BufRead};
-use std::path::Path;
-
-fn lines_from_file(filename: impl AsRef<Path>) -> io::Result<impl Iterator<Item = String>> {
-    let file = File::open(&filename)?;
-    let reader = io::BufReader::new(file);
-    Ok(reader.lines().map(|l| l.expect("Could not parse line")))
-}
+use crate::error::Error;
+use crate::grid::Grid;
+use crate::input::InputSource;
+use crate::solution::{Answer, Solution};
+
+const DAY: u32 = 11;
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
-enum SpaceType {
+pub enum SpaceType {
     Empty,
     Galaxy,
 }
 
-type Space = Vec<Vec<SpaceType>>;
-
-fn parse_line(line: &str) -> Vec<SpaceType> {
-    use SpaceType::*;
-    line.chars()
-        .map(|c| match c {
-            '#' => Galaxy,
-            '.' => Empty,
-            x => panic!("Char {}", x),
-        })
-        .collect()
-}
-
-fn parse() -> Space {
-    lines_from_file("src/day11.txt")
-        .unwrap()
-        .map(|l| parse_line(&l))
-        .collect()
-}
+type Space = Grid<SpaceType>;
 
-pub fn task1() -> crate::AOCResult<usize> {
-    let mut space = parse();
-    expand_rows(&mut space);
-    expand_cols(&mut space);
-    let galaxies = find_galaxies(&space);
-    let sum_distance = calulate_distances(&galaxies);
-
-    crate::AOCResult {
-        day: 11,
-        task: 1,
-        r: sum_distance,
+fn parse_space(c: char) -> Option<SpaceType> {
+    match c {
+        '#' => Some(SpaceType::Galaxy),
+        '.' => Some(SpaceType::Empty),
+        _ => None,
     }
 }
 
-fn calulate_distances(galaxies: &[(usize, usize)]) -> usize {
-    let len = galaxies.len();
+pub struct Day11;
 
-    let mut res = 0;
+impl Solution for Day11 {
+    const DAY: u32 = DAY;
 
-    for i in 0..len {
-        let start = galaxies[i];
-        for end in galaxies.iter().take(len).skip(i + 1) {
-            let distance =
-                start.0.max(end.0) - start.0.min(end.0) + start.1.max(end.1) - start.1.min(end.1);
-            res += distance;
-        }
+    type Input = Space;
+
+    fn parse(input: &InputSource) -> Result<Self::Input, Error> {
+        Ok(Grid::parse(
+            DAY,
+            1,
+            &input.grid()?,
+            parse_space,
+            "'#' or '.'",
+        )?)
     }
-    res
-}
 
-fn find_galaxies(space: &Space) -> Vec<(usize, usize)> {
-    space
-        .iter()
-        .enumerate()
-        .flat_map(|(row, line)| {
-            line.iter()
-                .enumerate()
-                .filter(|(_, t)| **t == SpaceType::Galaxy)
-                .map(move |(col, _)| (row, col))
-        })
-        .collect()
-}
+    fn part1(space: &Self::Input) -> Result<Answer, Error> {
+        let sum_distance = sum_of_distances(space, 2);
 
-fn expand_rows(space: &mut Space) {
-    let mut len = space.len();
-    let mut index = 0;
-    while index < len {
-        if space[index].iter().all(|t| *t == SpaceType::Empty) {
-            space.insert(index, space[index].clone());
-            index += 1;
-            len += 1;
-        }
-        index += 1;
+        Ok(Answer::from(sum_distance))
     }
-}
 
-fn expand_cols(space: &mut Space) {
-    let mut len = space[0].len();
-    let mut index = 0;
-    while index < len {
-        let mut all_empty = true;
-        for row in space.iter() {
-            all_empty &= row[index] == SpaceType::Empty;
-        }
-
-        if all_empty {
-            for row in space.iter_mut() {
-                row.insert(index, SpaceType::Empty);
-            }
+    fn part2(space: &Self::Input) -> Result<Answer, Error> {
+        let sum_distance = sum_of_distances(space, 1000000);
 
-            index += 1;
-            len += 1;
-        }
-        index += 1;
+        Ok(Answer::from(sum_distance))
     }
 }
 
-pub fn task2() -> crate::AOCResult<u64> {
-    let space = parse();
-    let row_values = calculate_row_values(&space);
-    let col_values = calculate_column_values(&space);
-    let galaxies = find_galaxies(&space);
-    let sum_distance = calulate_distances_2(&galaxies, row_values, col_values);
-
-    crate::AOCResult {
-        day: 11,
-        task: 2,
-        r: sum_distance,
-    }
+/// The sum of the distances between all pairs of galaxies after every empty row
+/// and column has grown to `expansion` rows or columns.
+fn sum_of_distances(space: &Space, expansion: u64) -> u64 {
+    let row_values = line_values(space.rows().map(|row| row.iter()), expansion);
+    let col_values = line_values(space.columns(), expansion);
+    let galaxies = find_galaxies(space);
+    calulate_distances(&galaxies, row_values, col_values)
+}
+
+fn find_galaxies(space: &Space) -> Vec<(usize, usize)> {
+    space
+        .iter()
+        .filter(|(_, t)| **t == SpaceType::Galaxy)
+        .map(|(pos, _)| pos)
+        .collect()
 }
 
-fn calulate_distances_2(
+fn calulate_distances(
     galaxies: &[(usize, usize)],
     row_values: Vec<u64>,
     col_values: Vec<u64>,
@@ -149,12 +92,15 @@ fn calulate_distances_2(
     res
 }
 
-fn calculate_row_values(space: &Space) -> Vec<u64> {
-    space
-        .iter()
-        .map(|line| {
-            if line.iter().all(|t| *t == SpaceType::Empty) {
-                1000000
+/// The size of every row or column after the expansion.
+fn line_values<'a>(
+    lines: impl Iterator<Item = impl Iterator<Item = &'a SpaceType>>,
+    expansion: u64,
+) -> Vec<u64> {
+    lines
+        .map(|mut line| {
+            if line.all(|t| *t == SpaceType::Empty) {
+                expansion
             } else {
                 1
             }
@@ -162,14 +108,24 @@ fn calculate_row_values(space: &Space) -> Vec<u64> {
         .collect()
 }
 
-fn calculate_column_values(space: &Space) -> Vec<u64> {
-    (0..space.len())
-        .map(|i| {
-            if space.iter().all(|line| line[i] == SpaceType::Empty) {
-                1000000
-            } else {
-                1
-            }
-        })
-        .collect()
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::solution::example;
+
+    #[test]
+    fn example_part1() {
+        assert_eq!(
+            example::<Day11>(include_str!("examples/day11.txt"), 1),
+            Answer(374)
+        );
+    }
+
+    #[test]
+    fn example_part2() {
+        assert_eq!(
+            example::<Day11>(include_str!("examples/day11.txt"), 2),
+            Answer(82000210)
+        );
+    }
 }
@@ -1,63 +1,81 @@
-use std::fs::File;
-use std::io::{self, BufRead};
-use std::path::Path;
-
-fn lines_from_file(filename: impl AsRef<Path>) -> io::Result<impl Iterator<Item = String>> {
-    let file = File::open(&filename)?;
-    let reader = io::BufReader::new(file);
-    Ok(reader.lines().map(|l| l.expect("Could not parse line")))
-}
+use crate::error::Error;
+use crate::input::InputSource;
+use crate::parse::{self, ParseError};
+use crate::solution::{Answer, Solution};
+
+const DAY: u32 = 12;
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
-enum HotSpringStatus {
+pub enum HotSpringStatus {
     Operational,
     Damaged,
     Unknown,
 }
 
-fn parse() -> Vec<(Vec<HotSpringStatus>, Vec<usize>)> {
-    lines_from_file("src/day12.txt")
-        .unwrap()
-        .map(|line| parse_line(&line))
-        .collect()
-}
+type Record = (Vec<HotSpringStatus>, Vec<usize>);
 
-fn parse_line(line: &str) -> (Vec<HotSpringStatus>, Vec<usize>) {
+fn parse_line(line: &str) -> Result<Record, ParseError> {
     let mut iter = line.split_ascii_whitespace();
-    let hss = iter
-        .next()
-        .unwrap()
+    let hss = parse::next(&mut iter, "a row of springs")?
         .chars()
         .map(|c| match c {
-            '?' => HotSpringStatus::Unknown,
-            '.' => HotSpringStatus::Operational,
-            '#' => HotSpringStatus::Damaged,
-            _ => panic!(),
+            '?' => Ok(HotSpringStatus::Unknown),
+            '.' => Ok(HotSpringStatus::Operational),
+            '#' => Ok(HotSpringStatus::Damaged),
+            c => Err(ParseError::new(&c.to_string(), "'?', '.' or '#'")),
         })
-        .collect();
+        .collect::<Result<_, _>>()?;
 
-    let operationals = iter
-        .next()
-        .unwrap()
+    let operationals = parse::next(&mut iter, "a list of group sizes")?
         .split(',')
-        .map(|digits| digits.parse::<usize>().unwrap())
-        .collect();
+        .map(parse::number)
+        .collect::<Result<_, _>>()?;
 
-    (hss, operationals)
+    Ok((hss, operationals))
 }
 
-pub fn task1() -> crate::AOCResult<i64> {
-    // 7541
-    let data = parse();
-    let count: i64 = data
-        .iter()
-        .map(|(hss, counts)| calculate_possibilites(hss, counts))
-        .sum();
-    // let count = brute_force(&data);
-    crate::AOCResult {
-        day: 12,
-        task: 1,
-        r: count,
+pub struct Day12;
+
+impl Solution for Day12 {
+    const DAY: u32 = DAY;
+
+    type Input = Vec<Record>;
+
+    fn parse(input: &InputSource) -> Result<Self::Input, Error> {
+        Ok(parse::lines(DAY, 1, &input.lines()?, parse_line)?)
+    }
+
+    fn part1(data: &Self::Input) -> Result<Answer, Error> {
+        // 7541
+        let count: i64 = data
+            .iter()
+            .map(|(hss, counts)| calculate_possibilites(hss, counts))
+            .sum();
+        // let count = brute_force(&data);
+        Ok(Answer::from(count))
+    }
+
+    fn part2(data: &Self::Input) -> Result<Answer, Error> {
+        let count: i64 = data
+            .iter()
+            .map(|(hss, combinations)| {
+                let mut extended_hss = hss.clone();
+                (0..4).for_each(|_| {
+                    extended_hss.push(HotSpringStatus::Unknown);
+                    extended_hss.extend(hss.iter());
+                });
+
+                let mut extended_combinations = combinations.clone();
+                (0..4).for_each(|_| {
+                    extended_combinations.extend(combinations);
+                });
+
+                (extended_hss, extended_combinations)
+            })
+            .map(|(hss, counts)| calculate_possibilites(&hss, &counts))
+            .sum();
+
+        Ok(Answer::from(count))
     }
 }
 
@@ -103,37 +121,10 @@ fn calculate_possibilites(hss: &[HotSpringStatus], combinations: &[usize]) -> i6
     last[status_orders.len() - 2]
 }
 
-pub fn task2() -> crate::AOCResult<i64> {
-    let data = parse();
-    let count: i64 = data
-        .iter()
-        .map(|(hss, combinations)| {
-            let mut extended_hss = hss.clone();
-            (0..4).for_each(|_| {
-                extended_hss.push(HotSpringStatus::Unknown);
-                extended_hss.extend(hss.iter());
-            });
-
-            let mut extended_combinations = combinations.clone();
-            (0..4).for_each(|_| {
-                extended_combinations.extend(combinations);
-            });
-
-            (extended_hss, extended_combinations)
-        })
-        .map(|(hss, counts)| calculate_possibilites(&hss, &counts))
-        .sum();
-
-    crate::AOCResult {
-        day: 12,
-        task: 2,
-        r: count,
-    }
-}
-
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::solution::example;
     use HotSpringStatus::*;
 
     #[test]
@@ -188,4 +179,20 @@ mod test {
             )
         );
     }
+
+    #[test]
+    fn example_part1() {
+        assert_eq!(
+            example::<Day12>(include_str!("examples/day12.txt"), 1),
+            Answer(21)
+        );
+    }
+
+    #[test]
+    fn example_part2() {
+        assert_eq!(
+            example::<Day12>(include_str!("examples/day12.txt"), 2),
+            Answer(525152)
+        );
+    }
 }
@@ -1,56 +1,34 @@
-use std::fs::File;
-use std::io::prelude::Read;
-use std::io::Result;
-
-fn read_file_to_string(filename: &str) -> Result<String> {
-    let mut file = File::open(filename)?;
-    let mut contents = String::new();
-    file.read_to_string(&mut contents)?;
-    Ok(contents)
-}
+use crate::error::Error;
+use crate::grid::Grid;
+use crate::input::InputSource;
+use crate::solution::{Answer, Solution};
+
+const DAY: u32 = 13;
 
 #[derive(Debug)]
-struct BitField {
+pub struct BitField {
     rows: Vec<u64>,
     cols: Vec<u64>,
 }
 
-#[cfg(windows)]
-const DOUBLE_LINE_ENDING: &str = "\r\n\r\n";
-#[cfg(not(windows))]
-const DOUBLE_LINE_ENDING: &str = "\n\n";
-
-fn parse() -> Vec<BitField> {
-    read_file_to_string("src/day13.txt")
-        .unwrap()
-        .split(DOUBLE_LINE_ENDING)
-        .map(|split| {
-            let rows: Vec<u64> = split.lines().map(parse_line).collect();
-            let len = split.lines().next().unwrap().len();
-
-            let cols = (0..len)
-                .map(|col| {
-                    rows.iter().enumerate().fold(0, |acc, (row, n)| {
-                        acc + if (n & 1 << col) != 0 { 1 << row } else { 0 }
-                    })
-                })
-                .collect();
-
-            BitField { rows, cols }
-        })
-        .collect()
+/// Whether a tile is ash rather than rock.
+fn parse_tile(c: char) -> Option<bool> {
+    match c {
+        '.' => Some(true),
+        '#' => Some(false),
+        _ => None,
+    }
 }
 
-fn parse_line(line: &str) -> u64 {
-    line.char_indices().fold(0, |acc, c| {
-        if let (index, '.') = c {
-            return acc + (1 << index);
-        }
-        acc
-    })
+/// Packs a row or column into bits, setting the bit of every tile of ash.
+fn bits<'a>(line: impl Iterator<Item = &'a bool>) -> u64 {
+    line.enumerate().fold(
+        0,
+        |acc, (index, &ash)| if ash { acc | 1 << index } else { acc },
+    )
 }
 
-fn find_reflection_with_bit_flips(field: &Vec<u64>, bit_flips: u32) -> usize {
+fn find_reflection_with_bit_flips(field: &[u64], bit_flips: u32) -> usize {
     for i in 1..field.len() {
         let mut diff_cnt = 0;
         for j in 0..i.min(field.len() - i) {
@@ -63,40 +41,83 @@ fn find_reflection_with_bit_flips(field: &Vec<u64>, bit_flips: u32) -> usize {
     0
 }
 
-pub fn task1() -> crate::AOCResult<usize> {
-    let bit_fields = parse();
-    let num = bit_fields
-        .iter()
-        .map(|bf| {
-            (
-                find_reflection_with_bit_flips(&bf.rows, 0),
-                find_reflection_with_bit_flips(&bf.cols, 0),
-            )
-        })
-        .fold(0, |acc, (r, c)| acc + r * 100 + c);
-
-    crate::AOCResult {
-        day: 13,
-        task: 1,
-        r: num,
+pub struct Day13;
+
+impl Solution for Day13 {
+    const DAY: u32 = DAY;
+
+    type Input = Vec<BitField>;
+
+    fn parse(input: &InputSource) -> Result<Self::Input, Error> {
+        let mut bit_fields = Vec::new();
+        for block in input.blocks()? {
+            let grid = Grid::parse(
+                DAY,
+                block.first_line,
+                &block.lines,
+                parse_tile,
+                "'.' or '#'",
+            )?;
+            bit_fields.push(BitField {
+                rows: grid.rows().map(|row| bits(row.iter())).collect(),
+                // A vertical mirror is a horizontal one of the transposed pattern.
+                cols: grid
+                    .transpose()
+                    .rows()
+                    .map(|row| bits(row.iter()))
+                    .collect(),
+            });
+        }
+        Ok(bit_fields)
+    }
+
+    fn part1(bit_fields: &Self::Input) -> Result<Answer, Error> {
+        let num = bit_fields
+            .iter()
+            .map(|bf| {
+                (
+                    find_reflection_with_bit_flips(&bf.rows, 0),
+                    find_reflection_with_bit_flips(&bf.cols, 0),
+                )
+            })
+            .fold(0, |acc, (r, c)| acc + r * 100 + c);
+
+        Ok(Answer::from(num))
+    }
+
+    fn part2(bit_fields: &Self::Input) -> Result<Answer, Error> {
+        let num = bit_fields
+            .iter()
+            .map(|bf| {
+                (
+                    find_reflection_with_bit_flips(&bf.rows, 1),
+                    find_reflection_with_bit_flips(&bf.cols, 1),
+                )
+            })
+            .fold(0, |acc, (r, c)| acc + r * 100 + c);
+
+        Ok(Answer::from(num))
     }
 }
 
-pub fn task2() -> crate::AOCResult<usize> {
-    let bit_fields = parse();
-    let num = bit_fields
-        .iter()
-        .map(|bf| {
-            (
-                find_reflection_with_bit_flips(&bf.rows, 1),
-                find_reflection_with_bit_flips(&bf.cols, 1),
-            )
-        })
-        .fold(0, |acc, (r, c)| acc + r * 100 + c);
-
-    crate::AOCResult {
-        day: 13,
-        task: 2,
-        r: num,
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::solution::example;
+
+    #[test]
+    fn example_part1() {
+        assert_eq!(
+            example::<Day13>(include_str!("examples/day13.txt"), 1),
+            Answer(405)
+        );
+    }
+
+    #[test]
+    fn example_part2() {
+        assert_eq!(
+            example::<Day13>(include_str!("examples/day13.txt"), 2),
+            Answer(400)
+        );
     }
 }
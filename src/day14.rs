@@ -1,153 +1,49 @@
 use std::collections::HashMap;
-use std::fs::File;
-use std::io::{self, BufRead};
-use std::path::Path;
-
-fn lines_from_file(filename: impl AsRef<Path>) -> io::Result<impl Iterator<Item = String>> {
-    let file = File::open(&filename)?;
-    let reader = io::BufReader::new(file);
-    Ok(reader.lines().map(|l| l.expect("Could not parse line")))
-}
+
+use crate::error::Error;
+use crate::grid::Grid;
+use crate::input::InputSource;
+use crate::solution::{Answer, Solution};
+
+const DAY: u32 = 14;
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
-enum Space {
+pub enum Space {
     Empty,
     Rock,
     Wall,
 }
 
-fn parse() -> Vec<Vec<Space>> {
-    lines_from_file("src/day14.txt")
-        .unwrap()
-        .map(|line| parse_line(&line))
-        .collect()
-}
-
-fn parse_line(line: &str) -> Vec<Space> {
-    line.chars()
-        .map(|c| match c {
-            '.' => Space::Empty,
-            '#' => Space::Wall,
-            'O' => Space::Rock,
-            _ => panic!(),
-        })
-        .collect()
-}
-
-trait Tiltable {
-    fn row_len(&self) -> usize;
-    fn col_len(&self) -> usize;
-    fn get(&self, row: usize, col: usize) -> Space;
-    fn swap(&mut self, row1: usize, row2: usize, col: usize);
-}
-
-struct TiltNorth(Vec<Vec<Space>>);
-
-impl Tiltable for TiltNorth {
-    fn row_len(&self) -> usize {
-        self.0.len()
-    }
-
-    fn col_len(&self) -> usize {
-        self.0[0].len()
-    }
-
-    fn get(&self, row: usize, col: usize) -> Space {
-        self.0[row][col]
-    }
-
-    fn swap(&mut self, row1: usize, row2: usize, col: usize) {
-        let t = self.0[row1][col];
-        self.0[row1][col] = self.0[row2][col];
-        self.0[row2][col] = t;
-    }
-}
-
-struct TiltWest(Vec<Vec<Space>>);
-
-impl Tiltable for TiltWest {
-    fn row_len(&self) -> usize {
-        self.0[0].len()
-    }
-
-    fn col_len(&self) -> usize {
-        self.0.len()
-    }
-
-    fn get(&self, row: usize, col: usize) -> Space {
-        self.0[col][row]
-    }
-
-    fn swap(&mut self, row1: usize, row2: usize, col: usize) {
-        self.0[col].swap(row1, row2)
+fn parse_space(c: char) -> Option<Space> {
+    match c {
+        '.' => Some(Space::Empty),
+        '#' => Some(Space::Wall),
+        'O' => Some(Space::Rock),
+        _ => None,
     }
 }
 
-struct TiltSouth(Vec<Vec<Space>>);
-
-impl Tiltable for TiltSouth {
-    fn row_len(&self) -> usize {
-        self.0.len()
-    }
-
-    fn col_len(&self) -> usize {
-        self.0[0].len()
-    }
-
-    fn get(&self, row: usize, col: usize) -> Space {
-        self.0[self.0.len() - 1 - row][col]
-    }
-
-    fn swap(&mut self, row1: usize, row2: usize, col: usize) {
-        let start = self.0.len() - 1;
-        let t = self.0[start - row1][col];
-        self.0[start - row1][col] = self.0[start - row2][col];
-        self.0[start - row2][col] = t;
-    }
-}
-
-struct TiltEast(Vec<Vec<Space>>);
-
-impl Tiltable for TiltEast {
-    fn row_len(&self) -> usize {
-        self.0[0].len()
-    }
-
-    fn col_len(&self) -> usize {
-        self.0.len()
-    }
-
-    fn get(&self, row: usize, col: usize) -> Space {
-        self.0[col][self.row_len() - 1 - row]
-    }
-
-    fn swap(&mut self, row1: usize, row2: usize, col: usize) {
-        let start = self.row_len() - 1;
-        self.0[col].swap(start - row1, start - row2);
-    }
-}
-
-fn tilt<T: Tiltable>(tiltable: &mut T) {
-    for row_index in 0..tiltable.row_len() {
-        'loops: for column_index in 0..tiltable.col_len() {
-            if tiltable.get(row_index, column_index) != Space::Rock {
-                continue;
-            }
-            for swap_row_index in (0..row_index).rev() {
-                if tiltable.get(swap_row_index, column_index) != Space::Empty {
-                    tiltable.swap(swap_row_index + 1, row_index, column_index);
-                    continue 'loops;
+/// Rolls every rock as far north as it goes.
+fn tilt_north(platform: &mut Grid<Space>) {
+    for col in 0..platform.width() {
+        let mut free_row = 0;
+        for row in 0..platform.height() {
+            match platform[(row, col)] {
+                Space::Wall => free_row = row + 1,
+                Space::Rock => {
+                    platform.swap((free_row, col), (row, col));
+                    free_row += 1;
                 }
+                Space::Empty => {}
             }
-            tiltable.swap(0, row_index, column_index);
         }
     }
 }
 
-fn evaluate(platform: &Vec<Vec<Space>>) -> usize {
-    let value = platform.len();
+fn evaluate(platform: &Grid<Space>) -> usize {
+    let value = platform.height();
     platform
-        .iter()
+        .rows()
         .enumerate()
         .flat_map(|(index, row)| {
             row.iter()
@@ -157,20 +53,40 @@ fn evaluate(platform: &Vec<Vec<Space>>) -> usize {
         .sum()
 }
 
-pub fn task1() -> crate::AOCResult<usize> {
-    let platform = parse();
-    let mut tiltable = TiltNorth(platform);
-    tilt(&mut tiltable);
-    let result = evaluate(&tiltable.0);
+pub struct Day14;
+
+impl Solution for Day14 {
+    const DAY: u32 = DAY;
+
+    type Input = Grid<Space>;
+
+    fn parse(input: &InputSource) -> Result<Self::Input, Error> {
+        Ok(Grid::parse(
+            DAY,
+            1,
+            &input.grid()?,
+            parse_space,
+            "'.', '#' or 'O'",
+        )?)
+    }
+
+    fn part1(platform: &Self::Input) -> Result<Answer, Error> {
+        let mut platform = platform.clone();
+        tilt_north(&mut platform);
+        let result = evaluate(&platform);
+
+        Ok(Answer::from(result))
+    }
+
+    fn part2(platform: &Self::Input) -> Result<Answer, Error> {
+        let platform = tilt_ccw_circles(platform.clone(), 1000000000);
+        let result = evaluate(&platform);
 
-    crate::AOCResult {
-        day: 14,
-        task: 1,
-        r: result,
+        Ok(Answer::from(result))
     }
 }
 
-fn tilt_ccw_circles(mut platform: Vec<Vec<Space>>, times: usize) -> Vec<Vec<Space>> {
+fn tilt_ccw_circles(mut platform: Grid<Space>, times: usize) -> Grid<Space> {
     let mut cache = HashMap::new();
     let mut iterations = 0usize;
     while !cache.contains_key(&platform) {
@@ -192,26 +108,35 @@ fn tilt_ccw_circles(mut platform: Vec<Vec<Space>>, times: usize) -> Vec<Vec<Spac
     platform
 }
 
-fn tilt_ccw(platform: Vec<Vec<Space>>) -> Vec<Vec<Space>> {
-    let mut tiltable = TiltNorth(platform);
-    tilt(&mut tiltable);
-    let mut tiltable = TiltWest(tiltable.0);
-    tilt(&mut tiltable);
-    let mut tiltable = TiltSouth(tiltable.0);
-    tilt(&mut tiltable);
-    let mut tiltable = TiltEast(tiltable.0);
-    tilt(&mut tiltable);
-    tiltable.0
+/// Tilts the platform north, west, south and east. Turning it clockwise after
+/// every tilt brings the next side to the north, and after four turns it is
+/// back in place.
+fn tilt_ccw(mut platform: Grid<Space>) -> Grid<Space> {
+    for _ in 0..4 {
+        tilt_north(&mut platform);
+        platform = platform.rotate_clockwise();
+    }
+    platform
 }
 
-pub fn task2() -> crate::AOCResult<usize> {
-    let platform = parse();
-    let platform = tilt_ccw_circles(platform, 1000000000);
-    let result = evaluate(&platform);
-
-    crate::AOCResult {
-        day: 14,
-        task: 2,
-        r: result,
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::solution::example;
+
+    #[test]
+    fn example_part1() {
+        assert_eq!(
+            example::<Day14>(include_str!("examples/day14.txt"), 1),
+            Answer(136)
+        );
+    }
+
+    #[test]
+    fn example_part2() {
+        assert_eq!(
+            example::<Day14>(include_str!("examples/day14.txt"), 2),
+            Answer(64)
+        );
     }
 }
@@ -1,14 +1,11 @@
-use std::fs::File;
 use std::hash::Hasher;
-use std::io::prelude::Read;
-use std::io::Result;
-
-fn read_file_to_string(filename: &str) -> Result<String> {
-    let mut file = File::open(filename)?;
-    let mut contents = String::new();
-    file.read_to_string(&mut contents)?;
-    Ok(contents)
-}
+
+use crate::error::Error;
+use crate::input::InputSource;
+use crate::parse::{self, ParseError};
+use crate::solution::{Answer, Solution};
+
+const DAY: u32 = 15;
 
 struct StringWrapper<'a>(&'a str);
 
@@ -34,31 +31,78 @@ impl std::hash::Hasher for AOCHasher {
     }
 }
 
-fn parse() -> Vec<String> {
-    read_file_to_string("src/day15.txt")
-        .unwrap()
-        .split(',')
-        .map(|s| s.to_owned())
-        .collect()
+/// One step of the initialization sequence, as written for the hash of part 1 and
+/// as an operation on the lens with `label` for part 2.
+pub struct Step {
+    text: String,
+    label: String,
+    operation: Operation,
+}
+
+enum Operation {
+    Remove,
+    Insert { focal_strength: u64 },
+}
+
+fn parse_step(text: &str) -> Result<Step, ParseError> {
+    let (label, operation) = if let Some(label) = text.strip_suffix('-') {
+        (label, Operation::Remove)
+    } else {
+        let (label, focal_strength) = parse::split_once(text, "=")?;
+        let focal_strength = parse::number(focal_strength)?;
+        (label, Operation::Insert { focal_strength })
+    };
+    Ok(Step {
+        text: text.to_owned(),
+        label: label.to_owned(),
+        operation,
+    })
 }
 
-pub fn task1() -> crate::AOCResult<u64> {
-    use std::hash::Hash;
-    let s = parse();
-    let r: u64 = s
-        .iter()
-        .map(|s| {
-            let mut h = AOCHasher(0);
-            let sw = StringWrapper(s);
-            sw.hash(&mut h);
-            h.finish()
-        })
-        .sum();
-
-    crate::AOCResult {
-        day: 15,
-        task: 1,
-        r,
+pub struct Day15;
+
+impl Solution for Day15 {
+    const DAY: u32 = DAY;
+
+    type Input = Vec<Step>;
+
+    fn parse(input: &InputSource) -> Result<Self::Input, Error> {
+        Ok(input
+            .text()?
+            .trim_end()
+            .split(',')
+            .map(|s| parse_step(s).map_err(|e| e.in_day(DAY)))
+            .collect::<Result<_, _>>()?)
+    }
+
+    fn part1(steps: &Self::Input) -> Result<Answer, Error> {
+        use std::hash::Hash;
+        let r: u64 = steps
+            .iter()
+            .map(|step| {
+                let mut h = AOCHasher(0);
+                let sw = StringWrapper(&step.text);
+                sw.hash(&mut h);
+                h.finish()
+            })
+            .sum();
+
+        Ok(Answer::from(r))
+    }
+
+    fn part2(steps: &Self::Input) -> Result<Answer, Error> {
+        let mut hm = HM::new();
+        for step in steps {
+            match step.operation {
+                Operation::Remove => hm.remove(&step.label),
+                Operation::Insert { focal_strength } => hm.swap(Lens {
+                    name: step.label.clone(),
+                    focal_strength,
+                }),
+            }
+        }
+
+        Ok(Answer::from(hm.calculate_focusing_power()))
     }
 }
 
@@ -73,7 +117,7 @@ struct HM(Vec<Vec<Lens>>);
 
 impl HM {
     fn new() -> Self {
-        HM(vec![Vec::with_capacity(10); 256])
+        HM((0..256).map(|_| Vec::with_capacity(10)).collect())
     }
 
     fn swap(&mut self, lens: Lens) {
@@ -113,26 +157,37 @@ impl HM {
     }
 }
 
-pub fn task2() -> crate::AOCResult<u64> {
-    let mut hm = HM::new();
-    let s = parse();
-    s.iter().for_each(|s| {
-        if s.ends_with('-') {
-            hm.remove(&s[0..(s.len() - 1)])
-        } else {
-            let mut iter = s.split('=');
-            let name = iter.next().unwrap();
-            let focal_strength = iter.next().unwrap().parse::<u64>().unwrap();
-            hm.swap(Lens {
-                name: name.to_owned(),
-                focal_strength,
-            });
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::solution::example;
+
+    #[test]
+    fn example_part1() {
+        assert_eq!(
+            example::<Day15>(include_str!("examples/day15.txt"), 1),
+            Answer(1320)
+        );
+    }
+
+    #[test]
+    fn invalid_steps() {
+        for (text, expected) in [
+            ("rn=1,cm", "day 15: expected '=', found 'cm'"),
+            ("rn=1,cm=x", "day 15: expected a number, found 'x'"),
+        ] {
+            let Err(error) = Day15::parse(&InputSource::Text(text.to_owned())) else {
+                panic!("{}", text);
+            };
+            assert_eq!(error.to_string(), expected);
         }
-    });
+    }
 
-    crate::AOCResult {
-        day: 15,
-        task: 2,
-        r: hm.calculate_focusing_power(),
+    #[test]
+    fn example_part2() {
+        assert_eq!(
+            example::<Day15>(include_str!("examples/day15.txt"), 2),
+            Answer(145)
+        );
     }
 }
@@ -1,263 +1,133 @@
 use std::collections::HashSet;
-use std::fs::File;
-use std::io::{self, BufRead};
-use std::path::Path;
 
 use itertools::Itertools;
 
-fn lines_from_file(filename: impl AsRef<Path>) -> io::Result<impl Iterator<Item = String>> {
-    let file = File::open(&filename)?;
-    let reader = io::BufReader::new(file);
-    Ok(reader.lines().map(|l| l.expect("Could not parse line")))
-}
+use crate::error::Error;
+use crate::geometry::{Direction, Pos};
+use crate::grid::Grid;
+use crate::input::InputSource;
+use crate::solution::{Answer, Solution};
 
-fn parse() -> Vec<String> {
-    lines_from_file("src/day16.txt")
-        .unwrap()
-        .map(|l| l.to_owned())
-        .collect()
-}
+const DAY: u32 = 16;
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
-enum Direction {
-    North,
-    East,
-    South,
-    West,
-}
-
-#[derive(Debug, PartialEq, Eq, Hash, Clone)]
 struct State {
-    row: usize,
-    col: usize,
-    dir: Direction,
+    pos: Pos,
+    heading: Direction,
 }
 
 trait DirectBeam {
-    fn direct(&self, state: &State) -> Vec<State>;
+    fn direct(&self, heading: Direction) -> Vec<Direction>;
 }
 
-impl DirectBeam for u8 {
-    fn direct(&self, state: &State) -> Vec<State> {
+impl DirectBeam for char {
+    fn direct(&self, heading: Direction) -> Vec<Direction> {
+        let vertical = matches!(heading, Direction::North | Direction::South);
         match self {
-            // '.'
-            46 => match state.dir {
-                Direction::North => vec![State {
-                    row: state.row + 1,
-                    col: state.col,
-                    dir: state.dir,
-                }],
-                Direction::East => vec![State {
-                    row: state.row,
-                    col: state.col.wrapping_sub(1),
-                    dir: state.dir,
-                }],
-                Direction::South => vec![State {
-                    row: state.row.wrapping_sub(1),
-                    col: state.col,
-                    dir: state.dir,
-                }],
-                Direction::West => vec![State {
-                    row: state.row,
-                    col: state.col + 1,
-                    dir: state.dir,
-                }],
-            },
-            // '/'
-            47 => match state.dir {
-                Direction::North => vec![State {
-                    row: state.row,
-                    col: state.col.wrapping_sub(1),
-                    dir: Direction::East,
-                }],
-                Direction::East => vec![State {
-                    row: state.row + 1,
-                    col: state.col,
-                    dir: Direction::North,
-                }],
-                Direction::South => vec![State {
-                    row: state.row,
-                    col: state.col + 1,
-                    dir: Direction::West,
-                }],
-                Direction::West => vec![State {
-                    row: state.row.wrapping_sub(1),
-                    col: state.col,
-                    dir: Direction::South,
-                }],
-            },
-            // '\'
-            92 => match state.dir {
-                Direction::North => vec![State {
-                    row: state.row,
-                    col: state.col + 1,
-                    dir: Direction::West,
-                }],
-                Direction::East => vec![State {
-                    row: state.row.wrapping_sub(1),
-                    col: state.col,
-                    dir: Direction::South,
-                }],
-                Direction::South => vec![State {
-                    row: state.row,
-                    col: state.col.wrapping_sub(1),
-                    dir: Direction::East,
-                }],
-                Direction::West => vec![State {
-                    row: state.row + 1,
-                    col: state.col,
-                    dir: Direction::North,
-                }],
-            },
-            // '-'
-            45 => match state.dir {
-                Direction::North => vec![
-                    State {
-                        row: state.row,
-                        col: state.col + 1,
-                        dir: Direction::West,
-                    },
-                    State {
-                        row: state.row,
-                        col: state.col.wrapping_sub(1),
-                        dir: Direction::East,
-                    },
-                ],
-                Direction::East => vec![State {
-                    row: state.row,
-                    col: state.col.wrapping_sub(1),
-                    dir: state.dir,
-                }],
-                Direction::South => vec![
-                    State {
-                        row: state.row,
-                        col: state.col + 1,
-                        dir: Direction::West,
-                    },
-                    State {
-                        row: state.row,
-                        col: state.col.wrapping_sub(1),
-                        dir: Direction::East,
-                    },
-                ],
-                Direction::West => vec![State {
-                    row: state.row,
-                    col: state.col + 1,
-                    dir: state.dir,
-                }],
-            },
-            // '|'
-            124 => match state.dir {
-                Direction::North => vec![State {
-                    row: state.row + 1,
-                    col: state.col,
-                    dir: state.dir,
-                }],
-                Direction::East => vec![
-                    State {
-                        row: state.row + 1,
-                        col: state.col,
-                        dir: Direction::North,
-                    },
-                    State {
-                        row: state.row.wrapping_sub(1),
-                        col: state.col,
-                        dir: Direction::South,
-                    },
-                ],
-                Direction::South => vec![State {
-                    row: state.row.wrapping_sub(1),
-                    col: state.col,
-                    dir: state.dir,
-                }],
-                Direction::West => vec![
-                    State {
-                        row: state.row + 1,
-                        col: state.col,
-                        dir: Direction::North,
-                    },
-                    State {
-                        row: state.row.wrapping_sub(1),
-                        col: state.col,
-                        dir: Direction::South,
-                    },
-                ],
-            },
+            '.' => vec![heading],
+            '/' if vertical => vec![heading.turn_right()],
+            '/' => vec![heading.turn_left()],
+            '\\' if vertical => vec![heading.turn_left()],
+            '\\' => vec![heading.turn_right()],
+            '-' if vertical => vec![heading.turn_left(), heading.turn_right()],
+            '|' if !vertical => vec![heading.turn_left(), heading.turn_right()],
+            '-' | '|' => vec![heading],
             _ => panic!(),
         }
     }
 }
 
-fn calculate_energized_tiles(input: &[String], start: State) -> usize {
+fn calculate_energized_tiles(input: &Grid<char>, start: State) -> usize {
     let mut seen = HashSet::new();
-    let mut worklist = vec![start.clone()];
+    let mut worklist = vec![start];
     seen.insert(start);
     while let Some(s) = worklist.pop() {
-        let next_states = input[s.row].as_bytes()[s.col].direct(&s);
+        let headings = input[s.pos.into()].direct(s.heading);
         worklist.extend(
-            next_states
+            headings
                 .into_iter()
-                .filter(|n| n.row < input.len() && n.col < input[n.row].len())
-                .filter(|n| seen.insert(n.clone())),
+                .filter_map(|heading| {
+                    let pos = s.pos.step_within(heading, input.width(), input.height())?;
+                    Some(State { pos, heading })
+                })
+                .filter(|n| seen.insert(*n)),
         );
     }
 
-    seen.iter().map(|s| (s.row, s.col)).unique().count()
+    seen.iter().map(|s| s.pos).unique().count()
 }
 
-pub fn task1() -> crate::AOCResult<usize> {
-    let input = parse();
-    let start = State {
-        row: 0,
-        col: 0,
-        dir: Direction::West,
-    };
+pub struct Day16;
 
-    let result = calculate_energized_tiles(&input, start);
+impl Solution for Day16 {
+    const DAY: u32 = DAY;
 
-    crate::AOCResult {
-        day: 16,
-        task: 1,
-        r: result,
-    }
-}
+    type Input = Grid<char>;
 
-pub fn task2() -> crate::AOCResult<usize> {
-    let input = parse();
+    fn parse(input: &InputSource) -> Result<Self::Input, Error> {
+        Ok(Grid::parse_chars(DAY, &input.grid()?, ".|-/\\")?)
+    }
 
-    let mut result = 0;
-    for row in 0..(input.len()) {
+    fn part1(input: &Self::Input) -> Result<Answer, Error> {
         let start = State {
-            row,
-            col: 0,
-            dir: Direction::West,
+            pos: Pos::new(0, 0),
+            heading: Direction::East,
         };
-        result = result.max(calculate_energized_tiles(&input, start));
-        let start = State {
-            row,
-            col: input[row].len() - 1,
-            dir: Direction::East,
-        };
-        result = result.max(calculate_energized_tiles(&input, start));
+
+        let result = calculate_energized_tiles(input, start);
+
+        Ok(Answer::from(result))
     }
-    for col in 0..(input[0].len()) {
-        let start = State {
-            row: 0,
-            col,
-            dir: Direction::North,
-        };
-        result = result.max(calculate_energized_tiles(&input, start));
-        let start = State {
-            row: input.len() - 1,
-            col,
-            dir: Direction::South,
-        };
-        result = result.max(calculate_energized_tiles(&input, start));
+
+    fn part2(input: &Self::Input) -> Result<Answer, Error> {
+        let mut result = 0;
+        for row in 0..input.height() {
+            let start = State {
+                pos: Pos::new(row, 0),
+                heading: Direction::East,
+            };
+            result = result.max(calculate_energized_tiles(input, start));
+            let start = State {
+                pos: Pos::new(row, input.width() - 1),
+                heading: Direction::West,
+            };
+            result = result.max(calculate_energized_tiles(input, start));
+        }
+        for col in 0..input.width() {
+            let start = State {
+                pos: Pos::new(0, col),
+                heading: Direction::South,
+            };
+            result = result.max(calculate_energized_tiles(input, start));
+            let start = State {
+                pos: Pos::new(input.height() - 1, col),
+                heading: Direction::North,
+            };
+            result = result.max(calculate_energized_tiles(input, start));
+        }
+
+        Ok(Answer::from(result))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::solution::example;
+
+    #[test]
+    fn example_part1() {
+        assert_eq!(
+            example::<Day16>(include_str!("examples/day16.txt"), 1),
+            Answer(46)
+        );
     }
 
-    crate::AOCResult {
-        day: 16,
-        task: 2,
-        r: result,
+    #[test]
+    fn example_part2() {
+        assert_eq!(
+            example::<Day16>(include_str!("examples/day16.txt"), 2),
+            Answer(51)
+        );
     }
 }
@@ -1,159 +1,78 @@
-use std::collections::BinaryHeap;
-use std::collections::HashMap;
-use std::fs::File;
-use std::io::{self, BufRead};
-use std::path::Path;
+use crate::error::Error;
+use crate::geometry::{Direction, Pos};
+use crate::grid::Grid;
+use crate::input::InputSource;
+use crate::search::{self, Path};
+use crate::solution::{Answer, Solution};
 
-fn lines_from_file(filename: impl AsRef<Path>) -> io::Result<impl Iterator<Item = String>> {
-    let file = File::open(&filename)?;
-    let reader = io::BufReader::new(file);
-    Ok(reader.lines().map(|l| l.expect("Could not parse line")))
-}
-
-fn parse() -> Vec<String> {
-    lines_from_file("src/day17.txt")
-        .unwrap()
-        .map(|l| l.to_owned())
-        .collect()
-}
-
-#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
-enum Direction {
-    North,
-    East,
-    South,
-    West,
-}
+const DAY: u32 = 17;
 
 #[derive(Debug, Hash, PartialEq, Eq, Clone)]
 struct State {
-    row: usize,
-    col: usize,
+    pos: Pos,
     num_straight: usize,
     direction: Direction,
 }
 
-#[derive(Debug, Hash, PartialEq, Eq, Clone)]
-struct StateWithCost {
-    state: State,
-    cost: u32,
+/// The cheapest route to the bottom right block that ends in a state for which
+/// `can_stop` holds.
+fn calulate_shortest_distance<F: Fn(&Grid<u32>, &State) -> Vec<State>>(
+    input: &Grid<u32>,
+    succ_fn: F,
+    can_stop: impl Fn(&State) -> bool,
+) -> Option<Path<State, u32>> {
+    let end = Pos::new(input.height() - 1, input.width() - 1);
+    let starts = [Direction::South, Direction::East].map(|direction| State {
+        pos: Pos::new(0, 0),
+        direction,
+        num_straight: 0,
+    });
+    // Every step costs at least the least heat loss of any block.
+    let min_heat_loss = input.iter().map(|(_, &heat)| heat).min().unwrap_or(0);
+
+    search::shortest_path_astar(
+        starts,
+        |state| {
+            succ_fn(input, state)
+                .into_iter()
+                .map(|succ| {
+                    let edge_cost = input[succ.pos.into()];
+                    (succ, edge_cost)
+                })
+                .collect::<Vec<_>>()
+        },
+        |state| state.pos.manhattan_distance(end) as u32 * min_heat_loss,
+        |state| state.pos == end && can_stop(state),
+    )
 }
 
-impl Ord for StateWithCost {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        other.cost.cmp(&self.cost)
-    }
+fn crucible_path(input: &Grid<u32>) -> Result<Path<State, u32>, Error> {
+    calulate_shortest_distance(input, get_successors_task1, |_| true)
+        .ok_or_else(|| Error::no_solution(DAY, "path to the bottom right block"))
 }
 
-impl PartialOrd for StateWithCost {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
-    }
+fn ultra_crucible_path(input: &Grid<u32>) -> Result<Path<State, u32>, Error> {
+    // An ultra crucible also needs to move 4 blocks straight before it can stop.
+    calulate_shortest_distance(input, get_successors_task2, |state| state.num_straight >= 4)
+        .ok_or_else(|| Error::no_solution(DAY, "path to the bottom right block"))
 }
 
-fn calulate_shortest_distance<F: Fn(&Vec<String>, &State) -> Vec<State>>(
-    input: &Vec<String>,
-    succ_fn: F,
-    max_consecutive: usize,
-) -> u32 {
-    let mut costs: HashMap<State, u32> = HashMap::new();
-    let mut next = BinaryHeap::new();
-    for (i, _) in input.iter().enumerate() {
-        for j in 0..input[i].len() {
-            for k in 0..(max_consecutive + 1) {
-                costs.insert(
-                    State {
-                        row: i,
-                        col: j,
-                        num_straight: k,
-                        direction: Direction::North,
-                    },
-                    u32::MAX,
-                );
-                costs.insert(
-                    State {
-                        row: i,
-                        col: j,
-                        num_straight: k,
-                        direction: Direction::East,
-                    },
-                    u32::MAX,
-                );
-                costs.insert(
-                    State {
-                        row: i,
-                        col: j,
-                        num_straight: k,
-                        direction: Direction::South,
-                    },
-                    u32::MAX,
-                );
-                costs.insert(
-                    State {
-                        row: i,
-                        col: j,
-                        num_straight: k,
-                        direction: Direction::West,
-                    },
-                    u32::MAX,
-                );
-            }
-        }
+/// Draws the route onto the map: every block the crucible enters shows an arrow
+/// in the direction it moved, all other blocks show their heat loss.
+fn render_path(input: &Grid<u32>, path: &[State]) -> String {
+    let mut map = input.map(|&heat| char::from_digit(heat, 10).unwrap_or('?'));
+    for state in path.iter().skip(1) {
+        map[state.pos.into()] = match state.direction {
+            Direction::North => '^',
+            Direction::East => '>',
+            Direction::South => 'v',
+            Direction::West => '<',
+        };
     }
-    let s1 = StateWithCost {
-        state: State {
-            row: 0,
-            col: 0,
-            direction: Direction::South,
-            num_straight: 0,
-        },
-        cost: 0,
-    };
-    let s2 = StateWithCost {
-        state: State {
-            row: 0,
-            col: 0,
-            direction: Direction::East,
-            num_straight: 0,
-        },
-        cost: 0,
-    };
-    costs.insert(s1.state.clone(), 0);
-    costs.insert(s2.state.clone(), 0);
-    next.push(s1);
-    next.push(s2);
-
-    while let Some(s) = next.pop() {
-        // Already something with lower cost found
-        if *costs.get(&s.state).unwrap() < s.cost {
-            continue;
-        }
-
-        let cost = *costs.get(&s.state).unwrap();
-        for succ in succ_fn(input, &s.state) {
-            let edge_cost = (input[succ.row].as_bytes()[succ.col] - "0".as_bytes()[0]) as u32;
-            let next_cost = cost + edge_cost;
-            let last_cost = costs.get_mut(&succ).unwrap();
-            if *last_cost > next_cost {
-                *last_cost = next_cost;
-                next.push(StateWithCost {
-                    state: succ,
-                    cost: next_cost,
-                });
-            }
-        }
-    }
-
-    let r = costs
-        .iter()
-        .filter(|(s, _)| s.row == input.len() - 1 && s.col == input[s.row].len() - 1)
-        .map(|(_, v)| *v)
-        .min()
-        .unwrap();
-    r
+    map.to_string()
 }
 
-fn get_successors_task1(input: &Vec<String>, state: &State) -> Vec<State> {
+fn get_successors_task1(input: &Grid<u32>, state: &State) -> Vec<State> {
     let mut res = Vec::new();
     // straight
     if state.num_straight < 3 {
@@ -162,198 +81,74 @@ fn get_successors_task1(input: &Vec<String>, state: &State) -> Vec<State> {
         }
     };
 
-    if let Some(next) = get_left_successor(input, state) {
+    if let Some(next) = get_turn_successor(input, state, state.direction.turn_left()) {
         res.push(next);
     }
 
-    if let Some(next) = get_right_successor(input, state) {
+    if let Some(next) = get_turn_successor(input, state, state.direction.turn_right()) {
         res.push(next);
     }
 
     res
 }
 
-fn get_straight_successor(input: &Vec<String>, state: &State) -> Option<State> {
-    match state.direction {
-        Direction::North => {
-            if state.row > 0 {
-                Some(State {
-                    row: state.row - 1,
-                    col: state.col,
-                    direction: state.direction,
-                    num_straight: state.num_straight + 1,
-                })
-            } else {
-                None
-            }
-        }
-        Direction::East => {
-            if state.col < input[state.row].len() - 1 {
-                Some(State {
-                    row: state.row,
-                    col: state.col + 1,
-                    direction: state.direction,
-                    num_straight: state.num_straight + 1,
-                })
-            } else {
-                None
-            }
-        }
-        Direction::South => {
-            if state.row < input.len() - 1 {
-                Some(State {
-                    row: state.row + 1,
-                    col: state.col,
-                    direction: state.direction,
-                    num_straight: state.num_straight + 1,
-                })
-            } else {
-                None
-            }
-        }
-        Direction::West => {
-            if state.col > 0 {
-                Some(State {
-                    row: state.row,
-                    col: state.col - 1,
-                    direction: state.direction,
-                    num_straight: state.num_straight + 1,
-                })
-            } else {
-                None
-            }
-        }
-    }
+fn get_straight_successor(input: &Grid<u32>, state: &State) -> Option<State> {
+    Some(State {
+        pos: state
+            .pos
+            .step_within(state.direction, input.width(), input.height())?,
+        direction: state.direction,
+        num_straight: state.num_straight + 1,
+    })
 }
 
-fn get_left_successor(input: &Vec<String>, state: &State) -> Option<State> {
-    match state.direction {
-        Direction::North => {
-            if state.col > 0 {
-                Some(State {
-                    row: state.row,
-                    col: state.col - 1,
-                    direction: Direction::West,
-                    num_straight: 1,
-                })
-            } else {
-                None
-            }
-        }
-        Direction::East => {
-            if state.row > 0 {
-                Some(State {
-                    row: state.row - 1,
-                    col: state.col,
-                    direction: Direction::North,
-                    num_straight: 1,
-                })
-            } else {
-                None
-            }
-        }
-        Direction::South => {
-            if state.col < input[state.row].len() - 1 {
-                Some(State {
-                    row: state.row,
-                    col: state.col + 1,
-                    direction: Direction::East,
-                    num_straight: 1,
-                })
-            } else {
-                None
-            }
-        }
-        Direction::West => {
-            if state.row < input.len() - 1 {
-                Some(State {
-                    row: state.row + 1,
-                    col: state.col,
-                    direction: Direction::South,
-                    num_straight: 1,
-                })
-            } else {
-                None
-            }
-        }
-    }
+fn get_turn_successor(input: &Grid<u32>, state: &State, direction: Direction) -> Option<State> {
+    Some(State {
+        pos: state
+            .pos
+            .step_within(direction, input.width(), input.height())?,
+        direction,
+        num_straight: 1,
+    })
 }
 
-fn get_right_successor(input: &Vec<String>, state: &State) -> Option<State> {
-    match state.direction {
-        Direction::North => {
-            if state.col < input[state.row].len() - 1 {
-                Some(State {
-                    row: state.row,
-                    col: state.col + 1,
-                    direction: Direction::East,
-                    num_straight: 1,
-                })
-            } else {
-                None
-            }
-        }
-        Direction::East => {
-            if state.row < input.len() - 1 {
-                Some(State {
-                    row: state.row + 1,
-                    col: state.col,
-                    direction: Direction::South,
-                    num_straight: 1,
-                })
-            } else {
-                None
-            }
-        }
-        Direction::South => {
-            if state.col > 0 {
-                Some(State {
-                    row: state.row,
-                    col: state.col - 1,
-                    direction: Direction::West,
-                    num_straight: 1,
-                })
-            } else {
-                None
-            }
-        }
-        Direction::West => {
-            if state.row > 0 {
-                Some(State {
-                    row: state.row - 1,
-                    col: state.col,
-                    direction: Direction::North,
-                    num_straight: 1,
-                })
-            } else {
-                None
-            }
-        }
+pub struct Day17;
+
+impl Solution for Day17 {
+    const DAY: u32 = DAY;
+
+    type Input = Grid<u32>;
+
+    fn parse(input: &InputSource) -> Result<Self::Input, Error> {
+        Ok(Grid::parse(
+            DAY,
+            1,
+            &input.grid()?,
+            |c| c.to_digit(10),
+            "a digit",
+        )?)
     }
-}
 
-pub fn task1() -> crate::AOCResult<u32> {
-    let input = parse();
-    let r = calulate_shortest_distance(&input, get_successors_task1, 3);
-    crate::AOCResult {
-        day: 17,
-        task: 1,
-        r,
+    fn part1(input: &Self::Input) -> Result<Answer, Error> {
+        Ok(Answer::from(crucible_path(input)?.cost))
+    }
+
+    fn part2(input: &Self::Input) -> Result<Answer, Error> {
+        Ok(Answer::from(ultra_crucible_path(input)?.cost))
     }
-}
 
-pub fn task2() -> crate::AOCResult<u32> {
-    let input = parse();
-    // TODO: There's actually a bug here. We do not check that we end on the final tiles with a num_straight of at least 4.
-    let r = calulate_shortest_distance(&input, get_successors_task2, 10);
-    crate::AOCResult {
-        day: 17,
-        task: 2,
-        r,
+    fn explain(input: &Self::Input, part: u32) -> Result<(Answer, Option<String>), Error> {
+        let path = match part {
+            1 => crucible_path(input)?,
+            _ => ultra_crucible_path(input)?,
+        };
+        let route = render_path(input, &path.states);
+        let explanation = format!("heat loss {}\n{}\n", path.cost, route);
+        Ok((Answer::from(path.cost), Some(explanation)))
     }
 }
 
-fn get_successors_task2(input: &Vec<String>, state: &State) -> Vec<State> {
+fn get_successors_task2(input: &Grid<u32>, state: &State) -> Vec<State> {
     let mut res = Vec::new();
     // straight
     if state.num_straight < 10 {
@@ -363,15 +158,94 @@ fn get_successors_task2(input: &Vec<String>, state: &State) -> Vec<State> {
     };
 
     if state.num_straight >= 4 {
-        if let Some(next) = get_left_successor(input, state) {
+        if let Some(next) = get_turn_successor(input, state, state.direction.turn_left()) {
             res.push(next);
         }
     }
 
     if state.num_straight >= 4 {
-        if let Some(next) = get_right_successor(input, state) {
+        if let Some(next) = get_turn_successor(input, state, state.direction.turn_right()) {
             res.push(next);
         }
     }
     res
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::solution::{example, parse_example};
+
+    #[test]
+    fn example_part1() {
+        assert_eq!(
+            example::<Day17>(include_str!("examples/day17.txt"), 1),
+            Answer(102)
+        );
+    }
+
+    #[test]
+    fn example_part2() {
+        assert_eq!(
+            example::<Day17>(include_str!("examples/day17.txt"), 2),
+            Answer(94)
+        );
+    }
+
+    #[test]
+    fn render_example_path() {
+        let input = parse_example::<Day17>(include_str!("examples/day17.txt"));
+        let path = calulate_shortest_distance(&input, get_successors_task1, |_| true).unwrap();
+        assert_eq!(path.cost, 102);
+        let expected = "\
+2>>34^>>>1323
+32v>>>35v5623
+32552456v>>54
+3446585845v52
+4546657867v>6
+14385987984v4
+44578769877v6
+36378779796v>
+465496798688v
+456467998645v
+12246868655<v
+25465488877v5
+43226746555v>
+";
+        assert_eq!(render_path(&input, &path.states), expected);
+
+        let (answer, explanation) = Day17::explain(&input, 1).unwrap();
+        assert_eq!(answer, Answer(102));
+        assert_eq!(
+            explanation.unwrap(),
+            format!("heat loss 102\n{}\n", expected)
+        );
+        let (answer, explanation) = Day17::explain(&input, 2).unwrap();
+        assert_eq!(answer, Answer(94));
+        assert!(explanation.unwrap().starts_with("heat loss 94\n"));
+    }
+
+    #[test]
+    fn minimum_straight_line_before_stopping() {
+        // Without the rule at the end the cheapest route would finish with a single
+        // step into the last column, for a heat loss of 47.
+        let text = "111111111111\n999999999991\n999999999991\n999999999991\n999999999991\n";
+        assert_eq!(example::<Day17>(text, 2), Answer(71));
+
+        let input = parse_example::<Day17>(text);
+        let path = calulate_shortest_distance(&input, get_successors_task2, |_| true).unwrap();
+        assert_eq!(path.cost, 47);
+        let path =
+            calulate_shortest_distance(&input, get_successors_task2, |s| s.num_straight >= 4)
+                .unwrap();
+        assert!(path.states.last().unwrap().num_straight >= 4);
+        let expected = "\
+1>>>>>>>1111
+9999999v9991
+9999999v9991
+9999999v9991
+9999999v>>>>
+";
+        assert_eq!(render_path(&input, &path.states), expected);
+    }
+}
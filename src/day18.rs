@@ -1,51 +1,59 @@
 use std::collections::{BTreeMap, BTreeSet};
-use std::fs::File;
-use std::io::{self, BufRead};
-use std::path::Path;
 
 use itertools::Itertools;
 
-fn lines_from_file(filename: impl AsRef<Path>) -> io::Result<impl Iterator<Item = String>> {
-    let file = File::open(&filename)?;
-    let reader = io::BufReader::new(file);
-    Ok(reader.lines().map(|l| l.expect("Could not parse line")))
-}
+use crate::error::Error;
+use crate::geometry::Direction;
+use crate::input::InputSource;
+use crate::parse::{self, ParseError};
+use crate::solution::{Answer, Solution};
 
-fn parse() -> Vec<DigCommand> {
-    lines_from_file("src/day18.txt")
-        .unwrap()
-        .map(|l| parse_line(&l))
-        .collect()
-}
+const DAY: u32 = 18;
 
-fn parse_line(line: &str) -> DigCommand {
+/// The command as written and the one hidden in its color code.
+fn parse_line(line: &str) -> Result<(DigCommand, DigCommand), ParseError> {
     let mut iter = line.split_ascii_whitespace();
-    let dir = match iter.next().unwrap() {
+    let dir = match parse::next(&mut iter, "a direction")? {
         "U" => Direction::North,
         "R" => Direction::East,
         "D" => Direction::South,
         "L" => Direction::West,
-        x => panic!("{:?}", x),
+        x => return Err(ParseError::new(x, "one of U, R, D or L")),
     };
-    let len = iter.next().unwrap().parse::<u32>().unwrap();
-    let t = iter.next().unwrap();
-    let hex_code = t[1..t.len() - 1].to_owned();
-    DigCommand { len, dir, hex_code }
-}
-
-#[derive(Debug, Clone, Copy)]
-enum Direction {
-    North,
-    East,
-    South,
-    West,
+    let len = parse::number(parse::next(&mut iter, "a length")?)?;
+    let t = parse::next(&mut iter, "a color code")?;
+    let hex_code = parse::strip_suffix(parse::strip_prefix(t, "(")?, ")")?;
+    let digits = parse::strip_prefix(hex_code, "#")?;
+    if digits.len() != 6 || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(ParseError::new(t, "a color code like (#70c710)"));
+    }
+    let color_len = u32::from_str_radix(&digits[..5], 16).unwrap();
+    let color_dir = match &digits[5..] {
+        "0" => Direction::East,
+        "1" => Direction::South,
+        "2" => Direction::West,
+        "3" => Direction::North,
+        x => return Err(ParseError::new(x, "a direction digit between 0 and 3")),
+    };
+    Ok((
+        DigCommand { len, dir },
+        DigCommand {
+            len: color_len,
+            dir: color_dir,
+        },
+    ))
 }
 
 #[derive(Debug, Clone)]
-struct DigCommand {
+pub struct DigCommand {
     len: u32,
     dir: Direction,
-    hex_code: String,
+}
+
+pub struct DigPlan {
+    commands: Vec<DigCommand>,
+    /// The commands hidden in the color codes, for part 2.
+    color_commands: Vec<DigCommand>,
 }
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
@@ -59,38 +67,52 @@ fn calculate_surroundings(commands: &Vec<DigCommand>) -> BTreeSet<Range> {
     let mut pos = (0i64, 0i64);
     let mut range_set = BTreeSet::new();
     for command in commands {
-        pos = match command.dir {
-            Direction::North => (pos.0 - command.len as i64, pos.1),
-            Direction::East => {
-                range_set.insert(Range {
-                    row: pos.0,
-                    col_1: pos.1,
-                    col_2: pos.1 + command.len as i64,
-                });
-                (pos.0, pos.1 + command.len as i64)
-            }
-            Direction::South => (pos.0 + command.len as i64, pos.1),
-            Direction::West => {
-                range_set.insert(Range {
-                    row: pos.0,
-                    col_1: pos.1 - command.len as i64,
-                    col_2: pos.1,
-                });
-                (pos.0, pos.1 - command.len as i64)
-            }
+        let (d_row, d_col) = command.dir.delta();
+        let len = command.len as i64;
+        let end = (pos.0 + d_row as i64 * len, pos.1 + d_col as i64 * len);
+        if d_col != 0 {
+            range_set.insert(Range {
+                row: pos.0,
+                col_1: pos.1.min(end.1),
+                col_2: pos.1.max(end.1),
+            });
         }
+        pos = end;
     }
     range_set
 }
 
-pub fn task1() -> crate::AOCResult<i64> {
-    let commands = parse();
-    let ranges = calculate_surroundings(&commands);
-    let r2 = imscared(ranges);
-    crate::AOCResult {
-        day: 18,
-        task: 1,
-        r: r2,
+pub struct Day18;
+
+impl Solution for Day18 {
+    const DAY: u32 = DAY;
+
+    type Input = DigPlan;
+
+    fn parse(input: &InputSource) -> Result<Self::Input, Error> {
+        let lines = input.lines()?;
+        if lines.is_empty() {
+            return Err(ParseError::missing("a dig command").in_day(DAY).into());
+        }
+        let (commands, color_commands) = parse::lines(DAY, 1, &lines, parse_line)?
+            .into_iter()
+            .unzip();
+        Ok(DigPlan {
+            commands,
+            color_commands,
+        })
+    }
+
+    fn part1(plan: &Self::Input) -> Result<Answer, Error> {
+        let ranges = calculate_surroundings(&plan.commands);
+        let r2 = imscared(ranges);
+        Ok(Answer::from(r2))
+    }
+
+    fn part2(plan: &Self::Input) -> Result<Answer, Error> {
+        let ranges = calculate_surroundings(&plan.color_commands);
+        let r2 = imscared(ranges);
+        Ok(Answer::from(r2))
     }
 }
 
@@ -343,28 +365,40 @@ fn imscared(range_set: BTreeSet<Range>) -> i64 {
     count
 }
 
-pub fn task2() -> crate::AOCResult<i64> {
-    let mut commands = parse();
-    for command in commands.iter_mut() {
-        let distance =
-            usize::from_str_radix(&command.hex_code[1..command.hex_code.len() - 1], 16).unwrap();
-        let dir = match &command.hex_code[(command.hex_code.len() - 1)..command.hex_code.len()] {
-            "0" => Direction::East,
-            "1" => Direction::South,
-            "2" => Direction::West,
-            "3" => Direction::North,
-            _ => panic!(),
-        };
-        command.len = distance as u32;
-        command.dir = dir;
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::solution::example;
+
+    #[test]
+    fn example_part1() {
+        assert_eq!(
+            example::<Day18>(include_str!("examples/day18.txt"), 1),
+            Answer(62)
+        );
     }
 
-    let ranges = calculate_surroundings(&commands);
+    #[test]
+    fn invalid_plans() {
+        for (text, expected) in [
+            ("", "day 18: expected a dig command, found nothing"),
+            (
+                "R 6 (#70c714)",
+                "day 18, line 1: expected a direction digit between 0 and 3, found '4'",
+            ),
+        ] {
+            let Err(error) = Day18::parse(&InputSource::Text(text.to_owned())) else {
+                panic!("{}", text);
+            };
+            assert_eq!(error.to_string(), expected);
+        }
+    }
 
-    let r2 = imscared(ranges);
-    crate::AOCResult {
-        day: 18,
-        task: 2,
-        r: r2,
+    #[test]
+    fn example_part2() {
+        assert_eq!(
+            example::<Day18>(include_str!("examples/day18.txt"), 2),
+            Answer(952408144115)
+        );
     }
 }
@@ -1,22 +1,14 @@
-use std::collections::HashMap;
-use std::fs::File;
-use std::io::prelude::Read;
-use std::io::Result;
-
-fn read_file_to_string(filename: &str) -> Result<String> {
-    let mut file = File::open(filename)?;
-    let mut contents = String::new();
-    file.read_to_string(&mut contents)?;
-    Ok(contents)
-}
+use std::collections::{HashMap, HashSet};
+
+use crate::error::Error;
+use crate::input::InputSource;
+use crate::parse::{self, ParseError};
+use crate::solution::{Answer, Solution};
 
-#[cfg(windows)]
-const DOUBLE_LINE_ENDING: &str = "\r\n\r\n";
-#[cfg(not(windows))]
-const DOUBLE_LINE_ENDING: &str = "\n\n";
+const DAY: u32 = 19;
 
 #[derive(Debug)]
-struct Part {
+pub struct Part {
     x: u64,
     m: u64,
     a: u64,
@@ -74,14 +66,16 @@ impl Attribute {
     }
 }
 
-impl From<&str> for Attribute {
-    fn from(value: &str) -> Self {
+impl TryFrom<&str> for Attribute {
+    type Error = ParseError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
         match value {
-            "x" => Attribute::X,
-            "m" => Attribute::M,
-            "a" => Attribute::A,
-            "s" => Attribute::S,
-            x => panic!("{}", x),
+            "x" => Ok(Attribute::X),
+            "m" => Ok(Attribute::M),
+            "a" => Ok(Attribute::A),
+            "s" => Ok(Attribute::S),
+            x => Err(ParseError::new(x, "one of x, m, a or s")),
         }
     }
 }
@@ -161,7 +155,7 @@ impl ConditionalRule {
 }
 
 #[derive(Debug)]
-struct Workflow {
+pub struct Workflow {
     rules: Vec<ConditionalRule>,
     fallback_target: String,
 }
@@ -212,169 +206,192 @@ impl Workflow {
     }
 }
 
-fn parse() -> (HashMap<String, Workflow>, Vec<Part>) {
-    let s = read_file_to_string("src/day19.txt").unwrap();
-    let mut splits = s.split(DOUBLE_LINE_ENDING);
-    let workflows = parse_workflows(splits.next().unwrap());
-    let parts = parse_parts(splits.next().unwrap());
-    (workflows, parts)
-}
-
-fn parse_workflows(workflows: &str) -> HashMap<String, Workflow> {
-    workflows.lines().map(parse_workflow).collect()
-}
-
-fn parse_workflow(workflow: &str) -> (String, Workflow) {
-    let mut iter = workflow.split('{');
-    let name = iter.next().unwrap().to_owned();
-    let wf = iter.next().unwrap();
-    let wf = &wf[0..wf.len() - 1];
-    let iter = wf.split(',');
+fn parse_workflow(workflow: &str) -> Result<(String, Workflow), ParseError> {
+    let (name, wf) = parse::split_once(workflow, "{")?;
+    let wf = parse::strip_suffix(wf, "}")?;
     let mut rules = Vec::new();
     let mut fallback_target = "";
-    for rule_str in iter {
-        if let Some(condition_end_index) = rule_str.find(':') {
-            let target = &rule_str[condition_end_index + 1..rule_str.len()];
-            if let Some(operator_index) = rule_str.find('<') {
-                if let Ok(value) = rule_str[0..operator_index].parse::<u64>() {
-                    rules.push(ConditionalRule {
-                        condition: Condition {
-                            var: Attribute::from(
-                                &rule_str[operator_index + 1..condition_end_index],
-                            ),
-                            cmp: std::cmp::Ordering::Less,
-                            value,
-                            attribute_left: false,
-                        },
-                        target: target.to_owned(),
-                    })
-                } else {
-                    rules.push(ConditionalRule {
-                        condition: Condition {
-                            var: Attribute::from(&rule_str[0..operator_index]),
-                            cmp: std::cmp::Ordering::Less,
-                            value: rule_str[operator_index + 1..condition_end_index]
-                                .parse::<u64>()
-                                .unwrap(),
-                            attribute_left: true,
-                        },
-                        target: target.to_owned(),
-                    })
-                }
-            } else if let Some(operator_index) = rule_str.find('>') {
-                if let Ok(value) = rule_str[0..operator_index].parse::<u64>() {
-                    rules.push(ConditionalRule {
-                        condition: Condition {
-                            var: Attribute::from(
-                                &rule_str[operator_index + 1..condition_end_index],
-                            ),
-                            cmp: std::cmp::Ordering::Greater,
-                            value,
-                            attribute_left: false,
-                        },
-                        target: target.to_owned(),
-                    })
-                } else {
-                    rules.push(ConditionalRule {
-                        condition: Condition {
-                            var: Attribute::from(&rule_str[0..operator_index]),
-                            cmp: std::cmp::Ordering::Greater,
-                            value: rule_str[operator_index + 1..condition_end_index]
-                                .parse::<u64>()
-                                .unwrap(),
-                            attribute_left: true,
-                        },
-                        target: target.to_owned(),
-                    })
-                }
-            } else {
-                panic!("{}", rule_str);
-            }
+    for rule_str in wf.split(',') {
+        if let Some((condition, target)) = rule_str.split_once(':') {
+            rules.push(ConditionalRule {
+                condition: parse_condition(condition)?,
+                target: target.to_owned(),
+            });
         } else {
             fallback_target = rule_str;
         }
     }
-    (
-        name,
+    Ok((
+        name.to_owned(),
         Workflow {
             rules,
             fallback_target: fallback_target.to_owned(),
         },
-    )
+    ))
 }
 
-fn parse_parts(parts: &str) -> Vec<Part> {
-    parts.lines().map(parse_part).collect()
+fn parse_condition(condition: &str) -> Result<Condition, ParseError> {
+    let (left, right, cmp) = if let Some((left, right)) = condition.split_once('<') {
+        (left, right, std::cmp::Ordering::Less)
+    } else if let Some((left, right)) = condition.split_once('>') {
+        (left, right, std::cmp::Ordering::Greater)
+    } else {
+        return Err(ParseError::new(condition, "a condition using '<' or '>'"));
+    };
+    if let Ok(value) = left.parse::<u64>() {
+        Ok(Condition {
+            var: Attribute::try_from(right)?,
+            cmp,
+            value,
+            attribute_left: false,
+        })
+    } else {
+        Ok(Condition {
+            var: Attribute::try_from(left)?,
+            cmp,
+            value: parse::number(right)?,
+            attribute_left: true,
+        })
+    }
 }
 
-fn parse_part(line: &str) -> Part {
+fn parse_part(line: &str) -> Result<Part, ParseError> {
+    let line = parse::strip_suffix(parse::strip_prefix(line, "{")?, "}")?;
     let mut iter = line.split(',');
-    let part = iter.next().unwrap();
-    let attr_split = part.split('=');
-    let x = attr_split.last().unwrap().parse::<u64>().unwrap();
-    let part = iter.next().unwrap();
-    let attr_split = part.split('=');
-    let m = attr_split.last().unwrap().parse::<u64>().unwrap();
-    let part = iter.next().unwrap();
-    let attr_split = part.split('=');
-    let a = attr_split.last().unwrap().parse::<u64>().unwrap();
-    let part = iter.next().unwrap();
-    let attr_split = part.split('=');
-    let val_str = attr_split.last().unwrap();
-    let s = val_str[0..val_str.len() - 1].parse::<u64>().unwrap();
-    Part { x, m, a, s }
+    let mut next_value = |name: &str| -> Result<u64, ParseError> {
+        let attribute = parse::next(&mut iter, &format!("a value for {}", name))?;
+        parse::number(parse::strip_prefix(attribute, &format!("{}=", name))?)
+    };
+    let x = next_value("x")?;
+    let m = next_value("m")?;
+    let a = next_value("a")?;
+    let s = next_value("s")?;
+    Ok(Part { x, m, a, s })
 }
 
-pub fn task1() -> crate::AOCResult<u64> {
-    let (workflows, parts) = parse();
-    let sum: u64 = parts
-        .into_iter()
-        .filter(|part| {
-            let mut status = Status::Redirected("in".to_owned());
-            while let Status::Redirected(wf_name) = status {
-                let wf = workflows.get(&wf_name).unwrap();
-                status = wf.evaluate(part);
+/// Checks that the parts start in a workflow named "in" and that every rule sends
+/// them on to a workflow that exists. `first_line` is the line of the first one.
+fn check_targets(workflows: &[(String, Workflow)], first_line: usize) -> Result<(), ParseError> {
+    let names: HashSet<&str> = workflows.iter().map(|(name, _)| name.as_str()).collect();
+    if !names.contains("in") {
+        return Err(ParseError::missing("a workflow named 'in'").in_day(DAY));
+    }
+    for (index, (_, workflow)) in workflows.iter().enumerate() {
+        let targets = workflow.rules.iter().map(|rule| &rule.target);
+        for target in targets.chain([&workflow.fallback_target]) {
+            if target != "A" && target != "R" && !names.contains(target.as_str()) {
+                let error = ParseError::new(target, "A, R or the name of a workflow");
+                return Err(error.at(DAY, first_line + index));
             }
-            status == Status::Accepted
-        })
-        .map(|part| part.get_total_value())
-        .sum();
-
-    crate::AOCResult {
-        day: 19,
-        task: 1,
-        r: sum,
+        }
     }
+    Ok(())
 }
 
-pub fn task2() -> crate::AOCResult<i64> {
-    let (workflows, _) = parse();
-    let mut worklist = Vec::new();
-    worklist.push((
-        PartRange {
-            x: (1, 4000),
-            m: (1, 4000),
-            a: (1, 4000),
-            s: (1, 4000),
-        },
-        "in".to_owned(),
-    ));
-    let mut count = 0;
-    while let Some((range, wfid)) = worklist.pop() {
-        let wf = workflows.get(&wfid).unwrap();
-        let r = wf.evaluate_ranges(&range);
-        for (new_range, status) in r {
-            match status {
-                Status::Accepted => count += new_range.get_total_value(),
-                Status::Rejected => (),
-                Status::Redirected(x) => worklist.push((new_range, x)),
+pub struct Day19;
+
+impl Solution for Day19 {
+    const DAY: u32 = DAY;
+
+    type Input = (HashMap<String, Workflow>, Vec<Part>);
+
+    fn parse(input: &InputSource) -> Result<Self::Input, Error> {
+        let blocks = input.blocks()?;
+        let (workflows, parts) = match blocks.as_slice() {
+            [workflows, parts, ..] => (workflows, parts),
+            _ => {
+                let error = ParseError::missing("a block of workflows and a block of parts");
+                return Err(error.in_day(DAY).into());
+            }
+        };
+        let first_line = workflows.first_line;
+        let workflows = parse::lines(DAY, first_line, &workflows.lines, parse_workflow)?;
+        let parts = parse::lines(DAY, parts.first_line, &parts.lines, parse_part)?;
+        check_targets(&workflows, first_line)?;
+        Ok((workflows.into_iter().collect(), parts))
+    }
+
+    fn part1((workflows, parts): &Self::Input) -> Result<Answer, Error> {
+        let sum: u64 = parts
+            .iter()
+            .filter(|part| {
+                let mut status = Status::Redirected("in".to_owned());
+                while let Status::Redirected(wf_name) = status {
+                    let wf = workflows.get(&wf_name).unwrap();
+                    status = wf.evaluate(part);
+                }
+                status == Status::Accepted
+            })
+            .map(|part| part.get_total_value())
+            .sum();
+
+        Ok(Answer::from(sum))
+    }
+
+    fn part2((workflows, _): &Self::Input) -> Result<Answer, Error> {
+        let mut worklist = Vec::new();
+        worklist.push((
+            PartRange {
+                x: (1, 4000),
+                m: (1, 4000),
+                a: (1, 4000),
+                s: (1, 4000),
+            },
+            "in".to_owned(),
+        ));
+        let mut count = 0;
+        while let Some((range, wfid)) = worklist.pop() {
+            let wf = workflows.get(&wfid).unwrap();
+            let r = wf.evaluate_ranges(&range);
+            for (new_range, status) in r {
+                match status {
+                    Status::Accepted => count += new_range.get_total_value(),
+                    Status::Rejected => (),
+                    Status::Redirected(x) => worklist.push((new_range, x)),
+                }
             }
         }
+
+        Ok(Answer::from(count))
     }
+}
 
-    crate::AOCResult {
-        day: 19,
-        task: 2,
-        r: count,
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::solution::example;
+
+    #[test]
+    fn example_part1() {
+        assert_eq!(
+            example::<Day19>(include_str!("examples/day19.txt"), 1),
+            Answer(19114)
+        );
+    }
+
+    #[test]
+    fn example_part2() {
+        assert_eq!(
+            example::<Day19>(include_str!("examples/day19.txt"), 2),
+            Answer(167409079868000)
+        );
+    }
+
+    #[test]
+    fn unknown_workflows() {
+        let error = |text: &str| {
+            let Err(error) = Day19::parse(&InputSource::Text(text.to_owned())) else {
+                panic!("{}", text);
+            };
+            error.to_string()
+        };
+        assert_eq!(
+            error("in{x>10:ok,A}\nok{R}\nbad{x>10:zz,A}\n\n{x=1,m=2,a=3,s=4}\n"),
+            "day 19, line 3: expected A, R or the name of a workflow, found 'zz'"
+        );
+        assert_eq!(
+            error("start{A}\n\n{x=1,m=2,a=3,s=4}\n"),
+            "day 19: expected a workflow named 'in', found nothing"
+        );
     }
 }
@@ -1,91 +1,77 @@
-use std::fs::File;
-use std::io::{self, BufRead};
-use std::path::Path;
-
-fn lines_from_file(filename: impl AsRef<Path>) -> io::Result<impl Iterator<Item = String>> {
-    let file = File::open(&filename)?;
-    let reader = io::BufReader::new(file);
-    Ok(reader.lines().map(|l| l.expect("Could not parse line")))
-}
+use crate::error::Error;
+use crate::input::InputSource;
+use crate::parse::{self, ParseError};
+use crate::solution::{Answer, Solution};
 
-pub fn task1() -> crate::AOCResult<u32> {
-    let configuration = Configuration {
-        total_red: 12,
-        total_green: 13,
-        total_blue: 14,
-    };
-    let games: Vec<Game> = lines_from_file("src/day2.txt")
-        .unwrap()
-        .map(|l| line_to_game(&l))
-        .collect();
-
-    let sum = games
-        .iter()
-        .filter(|game| game.is_possible(&configuration))
-        .map(|game| game.id)
-        .fold(0, u32::wrapping_add);
-
-    crate::AOCResult {
-        day: 2,
-        task: 1,
-        r: sum,
+const DAY: u32 = 2;
+
+pub struct Day2;
+
+impl Solution for Day2 {
+    const DAY: u32 = DAY;
+
+    type Input = Vec<Game>;
+
+    fn parse(input: &InputSource) -> Result<Self::Input, Error> {
+        Ok(parse::lines(DAY, 1, &input.lines()?, line_to_game)?)
     }
-}
 
-pub fn task2() -> crate::AOCResult<u32> {
-    let games: Vec<Game> = lines_from_file("src/day2.txt")
-        .unwrap()
-        .map(|l| line_to_game(&l))
-        .collect();
-
-    let sum: u32 = games
-        .iter()
-        .map(|game| game.min_round())
-        .map(|r| r.red * r.blue * r.green)
-        .sum();
-
-    crate::AOCResult {
-        day: 2,
-        task: 2,
-        r: sum,
+    fn part1(games: &Self::Input) -> Result<Answer, Error> {
+        let configuration = Configuration {
+            total_red: 12,
+            total_green: 13,
+            total_blue: 14,
+        };
+
+        let sum = games
+            .iter()
+            .filter(|game| game.is_possible(&configuration))
+            .map(|game| game.id)
+            .fold(0, u32::wrapping_add);
+
+        Ok(Answer::from(sum))
+    }
+
+    fn part2(games: &Self::Input) -> Result<Answer, Error> {
+        let sum: u32 = games
+            .iter()
+            .map(|game| game.min_round())
+            .map(|r| r.red * r.blue * r.green)
+            .sum();
+
+        Ok(Answer::from(sum))
     }
 }
 
-fn line_to_game(line: &str) -> Game {
-    let index = &line[5..];
-    let mut iter = index.split(':');
-    let id = iter.next().unwrap().parse::<u32>().unwrap();
-    let games_str = iter.next().unwrap();
-    let game_str_iter = games_str.split(';');
-    let rounds = game_str_iter
+fn line_to_game(line: &str) -> Result<Game, ParseError> {
+    let (id, games_str) = parse::split_once(parse::strip_prefix(line, "Game ")?, ":")?;
+    let id = parse::number(id)?;
+    let rounds = games_str
+        .split(';')
         .map(str::trim)
         .map(substring_to_round)
-        .collect();
+        .collect::<Result<_, _>>()?;
 
-    Game { id, rounds }
+    Ok(Game { id, rounds })
 }
 
-fn substring_to_round(substr: &str) -> Round {
+fn substring_to_round(substr: &str) -> Result<Round, ParseError> {
     let mut round = Round {
         red: 0,
         green: 0,
         blue: 0,
     };
-    substr.split(',').for_each(|colors| {
+    for colors in substr.split(',') {
         let mut iter = colors.split_ascii_whitespace();
-        let num = iter.next().unwrap().parse::<u32>().unwrap();
-        let color_str = iter.next().unwrap();
-        if color_str == "red" {
-            round.red = num;
-        } else if color_str == "blue" {
-            round.blue = num;
-        } else if color_str == "green" {
-            round.green = num;
-        } else {
-            print!("Logical error: {}", color_str);
+        let num = parse::number(parse::next(&mut iter, "a number of cubes")?)?;
+        match parse::next(&mut iter, "a color")? {
+            "red" => round.red = num,
+            "blue" => round.blue = num,
+            "green" => round.green = num,
+            color => return Err(ParseError::new(color, "red, green or blue")),
         }
-    });
-    round
+    }
+    Ok(round)
 }
 
 #[derive(Debug)]
@@ -111,7 +97,7 @@ impl Round {
 }
 
 #[derive(Debug)]
-struct Game {
+pub struct Game {
     id: u32,
     rounds: Vec<Round>,
 }
@@ -143,3 +129,25 @@ impl Game {
         min_round
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::solution::example;
+
+    #[test]
+    fn example_part1() {
+        assert_eq!(
+            example::<Day2>(include_str!("examples/day2.txt"), 1),
+            Answer(8)
+        );
+    }
+
+    #[test]
+    fn example_part2() {
+        assert_eq!(
+            example::<Day2>(include_str!("examples/day2.txt"), 2),
+            Answer(2286)
+        );
+    }
+}
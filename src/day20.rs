@@ -1,14 +1,19 @@
-use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::fmt::Debug;
-use std::fs::File;
-use std::io::{self, BufRead};
-use std::path::Path;
 
-fn lines_from_file(filename: impl AsRef<Path>) -> io::Result<impl Iterator<Item = String>> {
-    let file = File::open(&filename)?;
-    let reader = io::BufReader::new(file);
-    Ok(reader.lines().map(|l| l.expect("Could not parse line")))
-}
+use crate::cycles::{self, Cycle};
+use crate::dot::Dot;
+use crate::error::Error;
+use crate::input::InputSource;
+use crate::parse::{self, ParseError};
+use crate::solution::{Answer, Solution};
+use crate::trace;
+
+const DAY: u32 = 20;
+
+/// How many presses part 2 simulates at most while waiting for a part of the
+/// network to repeat.
+const MAX_PRESSES: u64 = 1 << 20;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 enum PulseType {
@@ -25,27 +30,18 @@ impl PulseType {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
 struct Pulse {
     source: String,
     pulse_type: PulseType,
     target: String,
 }
 
-fn _append_dot_node(module: &dyn Module, s: &mut String) {
-    if let Some(targets) = module.get_targets() {
-        for target in targets {
-            s.push_str(&format!("{} -> {};\n", module.get_name(), target));
-        }
-    }
-}
-
-trait Module {
+trait Module: Debug {
     fn evaluate_pulse(&mut self, pulse: &Pulse) -> Option<Vec<Pulse>>;
-    fn get_name(&self) -> &str;
-    fn get_targets(&self) -> Option<&Vec<String>>;
-    fn reset(&mut self) {}
-    fn broke(&mut self) -> Option<&mut BTreeMap<String, PulseType>> {
-        None
+    /// The memory of the module, empty if it has none.
+    fn state(&self) -> Vec<PulseType> {
+        Vec::new()
     }
 }
 
@@ -92,22 +88,8 @@ impl Module for FlipFlopModule {
         }
     }
 
-    fn get_name(&self) -> &str {
-        &self.name
-    }
-
-    fn get_targets(&self) -> Option<&Vec<String>> {
-        Some(&self.targets)
-    }
-
-    fn reset(&mut self) {
-        self.last_pulse = PulseType::Low;
-    }
-}
-
-impl ModulePlus for FlipFlopModule {
-    fn as_module(&self) -> &dyn Module {
-        self
+    fn state(&self) -> Vec<PulseType> {
+        vec![self.last_pulse]
     }
 }
 
@@ -125,20 +107,6 @@ impl Module for BroadcastModule {
             pulse.pulse_type,
         ))
     }
-
-    fn get_name(&self) -> &str {
-        &self.name
-    }
-
-    fn get_targets(&self) -> Option<&Vec<String>> {
-        Some(&self.targets)
-    }
-}
-
-impl ModulePlus for BroadcastModule {
-    fn as_module(&self) -> &dyn Module {
-        self
-    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -171,28 +139,8 @@ impl Module for ConjunctionModule {
         Some(targets_to_pulses(&self.name, &self.targets, pulse))
     }
 
-    fn get_name(&self) -> &str {
-        &self.name
-    }
-
-    fn get_targets(&self) -> Option<&Vec<String>> {
-        Some(&self.targets)
-    }
-
-    fn reset(&mut self) {
-        for (_, v) in self.last_inputs.iter_mut() {
-            *v = PulseType::Low;
-        }
-    }
-
-    fn broke(&mut self) -> Option<&mut BTreeMap<String, PulseType>> {
-        Some(&mut self.last_inputs)
-    }
-}
-
-impl ModulePlus for ConjunctionModule {
-    fn as_module(&self) -> &dyn Module {
-        self
+    fn state(&self) -> Vec<PulseType> {
+        self.last_inputs.values().copied().collect()
     }
 }
 
@@ -203,22 +151,9 @@ impl Module for SinkModule {
     fn evaluate_pulse(&mut self, _: &Pulse) -> Option<Vec<Pulse>> {
         None
     }
-
-    fn get_name(&self) -> &str {
-        ""
-    }
-
-    fn get_targets(&self) -> Option<&Vec<String>> {
-        None
-    }
-}
-
-impl ModulePlus for SinkModule {
-    fn as_module(&self) -> &dyn Module {
-        self
-    }
 }
 
+#[derive(Clone, Copy, PartialEq, Eq)]
 enum ModuleType {
     Sink,
     Broadcast,
@@ -226,15 +161,16 @@ enum ModuleType {
     Conjunction,
 }
 
-struct ModuleInfo {
+#[derive(Clone)]
+pub struct ModuleInfo {
     name: String,
     module_type: ModuleType,
     targets: Vec<String>,
 }
 
-fn parse_line(line: &str) -> ModuleInfo {
-    let mut iter = line.split("->");
-    let module_id = iter.next().unwrap().trim();
+fn parse_line(line: &str) -> Result<ModuleInfo, ParseError> {
+    let (module_id, targets) = parse::split_once(line, "->")?;
+    let module_id = module_id.trim();
     let (name, module_type) = if module_id == "broadcaster" {
         (module_id.to_owned(), ModuleType::Broadcast)
     } else if module_id.starts_with('%') {
@@ -250,35 +186,22 @@ fn parse_line(line: &str) -> ModuleInfo {
     } else {
         (module_id.to_owned(), ModuleType::Sink)
     };
-    let targets = iter
-        .next()
-        .unwrap()
+    let targets = targets
         .split(',')
         .map(|s| s.trim())
         .map(|s| s.to_owned())
         .collect();
-    ModuleInfo {
+    Ok(ModuleInfo {
         name,
         module_type,
         targets,
-    }
+    })
 }
 
-trait ModulePlus: Module + Debug {
-    fn as_module(&self) -> &dyn Module;
-}
-
-type Network = HashMap<String, Box<dyn ModulePlus>>;
+type Network = HashMap<String, Box<dyn Module>>;
 type PredecessorMap = HashMap<String, Vec<String>>;
 
-fn parse() -> (Network, PredecessorMap) {
-    let module_infos: Vec<ModuleInfo> = lines_from_file("src/day20.txt")
-        .unwrap()
-        .map(|line| parse_line(&line))
-        .collect();
-
-    let mut modules: Network = HashMap::new();
-
+fn predecessors(module_infos: &[ModuleInfo]) -> PredecessorMap {
     let mut inputs = HashMap::new();
 
     module_infos.iter().for_each(|mi| {
@@ -289,8 +212,14 @@ fn parse() -> (Network, PredecessorMap) {
                 .push(mi.name.clone());
         })
     });
+    inputs
+}
 
-    for mi in module_infos {
+fn build_network(module_infos: &[ModuleInfo]) -> Network {
+    let mut modules: Network = HashMap::new();
+    let inputs = predecessors(module_infos);
+
+    for mi in module_infos.iter().cloned() {
         match mi.module_type {
             ModuleType::Sink => {
                 modules.insert(mi.name, Box::new(SinkModule {}));
@@ -326,156 +255,729 @@ fn parse() -> (Network, PredecessorMap) {
         modules.entry(k.clone()).or_insert(Box::new(SinkModule {}));
     }
 
-    (modules, inputs)
+    modules
 }
 
-fn push_button(network: &mut Network) -> (u64, u64) {
-    let mut pulses = VecDeque::new();
-    pulses.push_back(Pulse {
-        source: "button".to_owned(),
-        pulse_type: PulseType::Low,
-        target: "broadcaster".to_owned(),
-    });
+/// A pulse that was processed during a press of the button.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct RecordedPulse {
+    /// The press the pulse belongs to, counted from 1.
+    press: u64,
+    /// The position of the pulse in the queue of its press, counted from 0.
+    position: usize,
+    /// How many pulses lead from the button to this one, counted from 0. The queue
+    /// processes the pulses in the order of their depth.
+    depth: usize,
+    pulse: Pulse,
+}
+
+/// Every pulse of a number of presses, in the order they were processed.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+struct Trace {
+    pulses: Vec<RecordedPulse>,
+}
 
-    let mut num_pulses = (0, 0);
+impl From<Trace> for trace::Trace {
+    fn from(trace: Trace) -> Self {
+        let signals = trace
+            .pulses
+            .into_iter()
+            .map(|recorded| trace::Signal {
+                press: recorded.press,
+                position: recorded.position,
+                source: recorded.pulse.source,
+                target: recorded.pulse.target,
+                high: recorded.pulse.pulse_type == PulseType::High,
+            })
+            .collect();
+        trace::Trace { signals }
+    }
+}
 
-    while let Some(pulse) = pulses.pop_front() {
-        let module = network.get_mut(&pulse.target).unwrap();
-        match pulse.pulse_type {
-            PulseType::Low => num_pulses.0 += 1,
-            PulseType::High => num_pulses.1 += 1,
+/// The memory of every flip-flop and conjunction of a network at one point, to
+/// compare its state across presses.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Snapshot(Vec<PulseType>);
+
+/// A network of modules with their current memory, driven by the button.
+struct Simulator {
+    network: Network,
+    /// The module names in a fixed order for snapshots.
+    names: Vec<String>,
+    presses: u64,
+    /// Where every pulse is recorded, if requested.
+    trace: Option<Trace>,
+}
+
+impl Simulator {
+    fn new(module_infos: &[ModuleInfo]) -> Simulator {
+        let network = build_network(module_infos);
+        let mut names: Vec<String> = network.keys().cloned().collect();
+        names.sort();
+        Simulator {
+            network,
+            names,
+            presses: 0,
+            trace: None,
         }
+    }
 
-        if let Some(targets) = module.evaluate_pulse(&pulse) {
-            pulses.extend(targets);
+    /// Presses the button once and calls `on_pulse` with every pulse and its
+    /// position in the queue, in the order they are processed. Pulses to modules
+    /// that are not part of the network are passed on but go nowhere.
+    fn press(&mut self, mut on_pulse: impl FnMut(usize, &Pulse)) {
+        self.presses += 1;
+        let mut pulses = VecDeque::new();
+        let button = Pulse {
+            source: "button".to_owned(),
+            pulse_type: PulseType::Low,
+            target: "broadcaster".to_owned(),
+        };
+        pulses.push_back((button, 0));
+
+        let mut position = 0;
+        while let Some((pulse, depth)) = pulses.pop_front() {
+            on_pulse(position, &pulse);
+            if let Some(module) = self.network.get_mut(&pulse.target) {
+                if let Some(targets) = module.evaluate_pulse(&pulse) {
+                    pulses.extend(targets.into_iter().map(|target| (target, depth + 1)));
+                }
+            }
+            if let Some(trace) = &mut self.trace {
+                trace.pulses.push(RecordedPulse {
+                    press: self.presses,
+                    position,
+                    depth,
+                    pulse,
+                });
+            }
+            position += 1;
         }
     }
-    num_pulses
-}
 
-pub fn task1() -> crate::AOCResult<u64> {
-    let (mut network, _) = parse();
+    /// Records every pulse from now on, until the trace is taken.
+    fn start_trace(&mut self) {
+        self.trace.get_or_insert_with(Trace::default);
+    }
 
-    let mut sum = (0, 0);
-    for _ in 0..1000 {
-        let pulses = push_button(&mut network);
-        sum.0 += pulses.0;
-        sum.1 += pulses.1;
+    /// The pulses recorded since the trace was started, and stops recording.
+    fn take_trace(&mut self) -> Trace {
+        self.trace.take().unwrap_or_default()
     }
 
-    crate::AOCResult {
-        day: 20,
-        task: 1,
-        r: sum.0 * sum.1,
+    /// Presses the button `presses` times and records every pulse.
+    fn run(&mut self, presses: u64) -> Trace {
+        let outer = self.trace.take();
+        self.start_trace();
+        for _ in 0..presses {
+            self.press(|_, _| ());
+        }
+        let trace = self.take_trace();
+        self.trace = outer;
+        trace
+    }
+
+    /// The memory of all modules.
+    fn snapshot(&self) -> Snapshot {
+        Snapshot(
+            self.names
+                .iter()
+                .flat_map(|name| self.network[name].state())
+                .collect(),
+        )
     }
 }
 
-fn push_button_was_node_low(network: &mut Network, last_node: &str) -> bool {
-    let mut pulses = VecDeque::new();
-    pulses.push_back(Pulse {
-        source: "button".to_owned(),
-        pulse_type: PulseType::Low,
-        target: "broadcaster".to_owned(),
+/// `module` and all modules that can send pulses to it, directly or not.
+fn ancestors(predecessors: &PredecessorMap, module: &str) -> HashSet<String> {
+    let mut found = HashSet::from([module.to_owned()]);
+    let mut worklist = vec![module];
+    while let Some(module) = worklist.pop() {
+        for source in predecessors.get(module).into_iter().flatten() {
+            if found.insert(source.clone()) {
+                worklist.push(source);
+            }
+        }
+    }
+    found
+}
+
+/// Presses the button until the memory of `modules`, or of the whole network if
+/// `None`, repeats, and returns the presses whose pulses `is_hit`. Only `modules`
+/// and the modules that can send pulses to them are simulated, as the others cannot
+/// influence them. Gives up after `max_presses` presses without a repeat.
+fn find_cycle(
+    module_infos: &[ModuleInfo],
+    modules: Option<&HashSet<String>>,
+    max_presses: u64,
+    mut is_hit: impl FnMut(&Trace) -> bool,
+) -> Option<Cycle> {
+    let sub_network: Vec<ModuleInfo> = match modules {
+        Some(modules) => {
+            let predecessors = predecessors(module_infos);
+            let mut closed = HashSet::from(["broadcaster".to_owned()]);
+            for module in modules {
+                closed.extend(ancestors(&predecessors, module));
+            }
+            module_infos
+                .iter()
+                .filter(|mi| closed.contains(&mi.name))
+                .cloned()
+                .collect()
+        }
+        None => module_infos.to_vec(),
+    };
+    let mut simulator = Simulator::new(&sub_network);
+    cycles::find_cycle_within(max_presses, || {
+        let state = simulator.snapshot();
+        let hit = is_hit(&simulator.run(1));
+        (state, hit)
+    })
+}
+
+/// Whether any pulse in `trace` is of `pulse_type` and goes to `target`, from
+/// `source` if given.
+fn sends(trace: &Trace, source: Option<&str>, target: &str, pulse_type: PulseType) -> bool {
+    trace.pulses.iter().any(|recorded| {
+        let pulse = &recorded.pulse;
+        source.is_none_or(|source| pulse.source == source)
+            && pulse.target == target
+            && pulse.pulse_type == pulse_type
+    })
+}
+
+/// The number of low and high pulses sent during `presses` presses of the button.
+/// Once the network gets back into a state it was in before, the rest follows from
+/// the pulses of one cycle without pressing on.
+fn count_pulses(module_infos: &[ModuleInfo], presses: u64) -> (u64, u64) {
+    let mut simulator = Simulator::new(module_infos);
+    let mut counts = Vec::new();
+    let cycle = cycles::find_cycle_within(presses, || {
+        let state = simulator.snapshot();
+        let mut count = (0, 0);
+        simulator.press(|_, pulse| match pulse.pulse_type {
+            PulseType::Low => count.0 += 1,
+            PulseType::High => count.1 += 1,
+        });
+        counts.push(count);
+        (state, false)
     });
 
-    let mut num_pulses = (0, 0);
+    let sum = |from: usize, to: usize| {
+        counts[from..to]
+            .iter()
+            .fold((0, 0), |(low, high), &(l, h)| (low + l, high + h))
+    };
+    let Some(cycle) = cycle else {
+        return sum(0, counts.len());
+    };
+    // Every press from the offset on sends the same pulses as one of the cycle.
+    let rest = presses - cycle.offset;
+    let repeats = rest / cycle.length;
+    let (offset, length) = (cycle.offset as usize, cycle.length as usize);
+    let (cycle_low, cycle_high) = sum(offset, offset + length);
+    let (low, high) = sum(0, offset + (rest % cycle.length) as usize);
+    (low + repeats * cycle_low, high + repeats * cycle_high)
+}
+
+/// Splits the question whether `target` receives a low pulse from a conjunction
+/// into when each input of that conjunction sends it a high one. This only works
+/// if the inputs are driven by separate parts of the network, which then run
+/// independently of each other. It also needs the conjunction to remember a high
+/// pulse from all inputs at once in every press in which each sends one, which
+/// holds if each input does so in a single window of depths, the same in every
+/// press, and all windows overlap.
+fn split_at_conjunction(
+    module_infos: &[ModuleInfo],
+    target: &str,
+    pulse_type: PulseType,
+    max_presses: u64,
+) -> Option<Vec<Cycle>> {
+    let predecessors = predecessors(module_infos);
+    let [conjunction] = predecessors.get(target)?.as_slice() else {
+        return None;
+    };
+    let info = module_infos.iter().find(|mi| &mi.name == conjunction)?;
+    let inputs = predecessors.get(conjunction)?;
+    if pulse_type != PulseType::Low
+        || info.module_type != ModuleType::Conjunction
+        || inputs.len() < 2
+    {
+        return None;
+    }
 
-    let mut res = false;
-    while let Some(pulse) = pulses.pop_front() {
-        if pulse.target == last_node && pulse.pulse_type == PulseType::Low {
-            res = true;
+    let mut groups: Vec<HashSet<String>> = inputs
+        .iter()
+        .map(|input| ancestors(&predecessors, input))
+        .collect();
+    for group in &mut groups {
+        group.remove("broadcaster");
+        if group.contains(conjunction) {
+            return None;
         }
-        let module = network.get_mut(&pulse.target).unwrap();
-        match pulse.pulse_type {
-            PulseType::Low => num_pulses.0 += 1,
-            PulseType::High => num_pulses.1 += 1,
+    }
+    for (i, group) in groups.iter().enumerate() {
+        if groups[i + 1..]
+            .iter()
+            .any(|other| !group.is_disjoint(other))
+        {
+            return None;
         }
+    }
 
-        if let Some(targets) = module.evaluate_pulse(&pulse) {
-            pulses.extend(targets);
+    let mut cycles = Vec::new();
+    let mut windows = Vec::new();
+    for input in inputs {
+        let group = HashSet::from([input.clone()]);
+        let mut seen = HashSet::new();
+        let cycle = find_cycle(module_infos, Some(&group), max_presses, |trace| {
+            let high = high_windows(trace, input, conjunction);
+            let hit = !high.is_empty();
+            if hit {
+                seen.insert(high);
+            }
+            hit
+        })?;
+        let seen: Vec<_> = seen.into_iter().collect();
+        let [high] = seen.as_slice() else {
+            return None;
+        };
+        let [(from, Some(to))] = high.as_slice() else {
+            return None;
+        };
+        cycles.push(cycle);
+        windows.push((*from, *to));
+    }
+    // The last high pulse arrives before the first low one.
+    let last_high = windows.iter().map(|&(from, _)| from).max()?;
+    let first_low = windows.iter().map(|&(_, to)| to).min()?;
+    (last_high < first_low).then_some(cycles)
+}
+
+/// The depths of the pulses during which `conjunction` remembers a high pulse from
+/// `input` in the press of `trace`, from the high pulse to the next low one, or to
+/// the end of the press if there is none.
+fn high_windows(trace: &Trace, input: &str, conjunction: &str) -> Vec<(usize, Option<usize>)> {
+    let mut windows = Vec::new();
+    let mut from = None;
+    for recorded in &trace.pulses {
+        let pulse = &recorded.pulse;
+        if pulse.source != input || pulse.target != conjunction {
+            continue;
         }
-    }
-    res
-}
-
-fn _print_dot_graph(network: &Network) {
-    let mut s = String::new();
-    s.push_str("digraph {\n");
-    s.push_str("button -> broadcaster;\n");
-    for e in network {
-        let module: &dyn ModulePlus = e.1.as_ref();
-        _append_dot_node(module.as_module(), &mut s);
-    }
-
-    s.push('}');
-
-    println!("{}", s);
-}
-
-fn iterator_broadcast_single(network: &mut Network, last_id: String, pre_last_id: String) -> u64 {
-    let mut num_cnt: u64 = 1;
-    let reset_values = network
-        .get_mut(&pre_last_id)
-        .unwrap()
-        .broke()
-        .unwrap()
-        .clone();
-
-    for keys in reset_values.keys() {
-        network
-            .get_mut(&pre_last_id)
-            .unwrap()
-            .broke()
-            .unwrap()
-            .clear();
-        network
-            .get_mut(&pre_last_id)
-            .unwrap()
-            .broke()
-            .unwrap()
-            .insert(keys.clone(), PulseType::Low);
-
-        let mut cnt = 0;
-        loop {
-            cnt += 1;
-            if push_button_was_node_low(network, &last_id) {
-                break;
+        match pulse.pulse_type {
+            PulseType::High => {
+                from.get_or_insert(recorded.depth);
+            }
+            PulseType::Low => {
+                if let Some(from) = from.take() {
+                    windows.push((from, Some(recorded.depth)));
+                }
             }
         }
+    }
+    windows.extend(from.map(|from| (from, None)));
+    windows
+}
+
+/// The first press of the button during which `target` receives a pulse of
+/// `pulse_type`, if there is one. Networks that do not repeat within `max_presses`
+/// presses need to be split at a conjunction in front of `target`, or give `None`.
+fn first_press_receiving(
+    module_infos: &[ModuleInfo],
+    target: &str,
+    pulse_type: PulseType,
+    max_presses: u64,
+) -> Option<u64> {
+    let cycles = match split_at_conjunction(module_infos, target, pulse_type, max_presses) {
+        Some(cycles) => cycles,
+        None => {
+            let modules = HashSet::from([target.to_owned()]);
+            vec![find_cycle(
+                module_infos,
+                Some(&modules),
+                max_presses,
+                |trace| sends(trace, None, target, pulse_type),
+            )?]
+        }
+    };
+    // Step 0 of a cycle is the first press.
+    cycles::first_common_hit(&cycles).map(|step| step + 1)
+}
 
-        network.iter_mut().for_each(|(_, v)| v.reset());
+pub struct Day20;
 
-        num_cnt *= cnt;
+impl Solution for Day20 {
+    const DAY: u32 = DAY;
+
+    type Input = Vec<ModuleInfo>;
+
+    fn parse(input: &InputSource) -> Result<Self::Input, Error> {
+        Ok(parse::lines(DAY, 1, &input.lines()?, parse_line)?)
     }
 
-    *network.get_mut(&pre_last_id).unwrap().broke().unwrap() = reset_values;
+    fn part1(module_infos: &Self::Input) -> Result<Answer, Error> {
+        let (low, high) = count_pulses(module_infos, 1000);
 
-    num_cnt
-}
+        Ok(Answer::from(low * high))
+    }
 
-fn find_last_and_pre_last_node_ids(
-    network: &Network,
-    predecessors: &PredecessorMap,
-) -> (String, String) {
-    let last_id = predecessors
-        .keys()
-        .find(|id| network.get(*id).unwrap().get_targets().is_none())
-        .unwrap();
-    let pre_last_id = &predecessors.get(last_id).unwrap()[0];
-    (last_id.clone(), pre_last_id.clone())
+    fn part2(module_infos: &Self::Input) -> Result<Answer, Error> {
+        let press = first_press_receiving(module_infos, "rx", PulseType::Low, MAX_PRESSES)
+            .ok_or_else(|| Error::no_solution(DAY, "press during which rx receives a low pulse"))?;
+
+        Ok(Answer::from(press))
+    }
+
+    fn graph(module_infos: &Self::Input) -> Result<Option<Dot>, Error> {
+        Ok(Some(network_graph(module_infos)))
+    }
+
+    fn trace(module_infos: &Self::Input, presses: u64) -> Result<Option<trace::Trace>, Error> {
+        Ok(Some(Simulator::new(module_infos).run(presses).into()))
+    }
 }
 
-pub fn task2() -> crate::AOCResult<u64> {
-    let (mut network, predecessors) = parse();
+/// The modules as boxes for flip-flops and diamonds for conjunctions, with an edge
+/// to each of their targets.
+fn network_graph(module_infos: &[ModuleInfo]) -> Dot {
+    let mut dot = Dot::digraph();
+    dot.node("button", &[("shape", "plaintext")]);
+    let defined: HashSet<&str> = module_infos.iter().map(|mi| mi.name.as_str()).collect();
+    for mi in module_infos {
+        let (shape, label) = match mi.module_type {
+            ModuleType::Sink => ("ellipse", mi.name.clone()),
+            ModuleType::Broadcast => ("doublecircle", mi.name.clone()),
+            ModuleType::FlipFlop => ("box", format!("%{}", mi.name)),
+            ModuleType::Conjunction => ("diamond", format!("&{}", mi.name)),
+        };
+        dot.node(&mi.name, &[("shape", shape), ("label", &label)]);
+    }
+    let mut sinks: Vec<&str> = module_infos
+        .iter()
+        .flat_map(|mi| mi.targets.iter().map(String::as_str))
+        .filter(|target| !defined.contains(target))
+        .collect();
+    sinks.sort();
+    sinks.dedup();
+    for sink in sinks {
+        dot.node(sink, &[("shape", "ellipse")]);
+    }
 
-    // _print_dot_graph(&_network);
+    dot.edge("button", "broadcaster", &[]);
+    for mi in module_infos {
+        for target in &mi.targets {
+            dot.edge(&mi.name, target, &[]);
+        }
+    }
+    dot
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::solution::{example, parse_example};
+    use crate::trace::TraceFormat;
+
+    #[test]
+    fn example_part1() {
+        assert_eq!(
+            example::<Day20>(include_str!("examples/day20.txt"), 1),
+            Answer(32000000)
+        );
+    }
+
+    #[test]
+    fn second_example_part1() {
+        assert_eq!(
+            example::<Day20>(include_str!("examples/day20_2.txt"), 1),
+            Answer(11687500)
+        );
+    }
+
+    /// Presses the button until `target` receives a pulse of `pulse_type`.
+    fn brute_force(
+        module_infos: &[ModuleInfo],
+        target: &str,
+        pulse_type: PulseType,
+    ) -> Option<u64> {
+        let mut simulator = Simulator::new(module_infos);
+        (1..=1000).find(|_| {
+            let mut found = false;
+            simulator.press(|_, pulse| {
+                found |= pulse.target == target && pulse.pulse_type == pulse_type
+            });
+            found
+        })
+    }
 
-    let nodes_to_adjust = find_last_and_pre_last_node_ids(&network, &predecessors);
-    let result = iterator_broadcast_single(&mut network, nodes_to_adjust.0, nodes_to_adjust.1);
+    #[test]
+    fn record_pulses() {
+        let mut simulator =
+            Simulator::new(&parse_example::<Day20>(include_str!("examples/day20.txt")));
+        let pulses = simulator.run(2).pulses;
+        assert_eq!(pulses.len(), 24);
+        assert_eq!(
+            pulses[0],
+            RecordedPulse {
+                press: 1,
+                position: 0,
+                depth: 0,
+                pulse: Pulse {
+                    source: "button".to_owned(),
+                    pulse_type: PulseType::Low,
+                    target: "broadcaster".to_owned(),
+                },
+            }
+        );
+        assert_eq!((pulses[11].press, pulses[11].position), (1, 11));
+        assert_eq!((pulses[12].press, pulses[12].position), (2, 0));
+        assert_eq!(pulses[11].depth, 7);
+        // c -high-> inv makes inv send a low pulse to a.
+        assert_eq!(pulses[6].pulse.target, "inv");
+        assert_eq!(pulses[7].pulse.source, "inv");
+        assert_eq!(pulses[7].pulse.pulse_type, PulseType::Low);
+        // After a full cycle all flip-flops are off again.
+        assert_eq!(
+            simulator.snapshot(),
+            Simulator::new(&parse_example::<Day20>(include_str!("examples/day20.txt"))).snapshot()
+        );
+    }
+
+    #[test]
+    fn cycles_of_the_network() {
+        let module_infos = parse_example::<Day20>(include_str!("examples/day20_2.txt"));
+        let cycle = find_cycle(&module_infos, None, 100, |trace| {
+            sends(trace, None, "output", PulseType::Low)
+        })
+        .unwrap();
+        assert_eq!((cycle.offset, cycle.length), (0, 4));
+        assert_eq!(cycle.hits, vec![0, 2]);
+
+        // a flips on every press, so it repeats after two; b only flips when inv,
+        // which follows a, sends it a low pulse.
+        let a = HashSet::from(["a".to_owned()]);
+        let cycle = find_cycle(&module_infos, Some(&a), 100, |_| false).unwrap();
+        assert_eq!((cycle.offset, cycle.length), (0, 2));
+        let b = HashSet::from(["b".to_owned()]);
+        let cycle = find_cycle(&module_infos, Some(&b), 100, |_| false).unwrap();
+        assert_eq!((cycle.offset, cycle.length), (0, 4));
+    }
+
+    #[test]
+    fn pulse_counts_in_closed_form() {
+        let module_infos = parse_example::<Day20>(include_str!("examples/day20_2.txt"));
+        assert_eq!(count_pulses(&module_infos, 1000), (4250, 2750));
+        assert_eq!(
+            count_pulses(&module_infos, 1_000_000_000_000),
+            (4_250_000_000_000, 2_750_000_000_000)
+        );
+        // Against pressing the button every time.
+        let mut simulator = Simulator::new(&module_infos);
+        let mut counts = (0, 0);
+        for presses in 1..=10 {
+            simulator.press(|_, pulse| match pulse.pulse_type {
+                PulseType::Low => counts.0 += 1,
+                PulseType::High => counts.1 += 1,
+            });
+            assert_eq!(count_pulses(&module_infos, presses), counts);
+        }
+        assert_eq!(count_pulses(&module_infos, 0), (0, 0));
+    }
+
+    #[test]
+    fn trace_exports() {
+        let mut simulator =
+            Simulator::new(&parse_example::<Day20>(include_str!("examples/day20.txt")));
+        simulator.press(|_, _| ());
+        simulator.start_trace();
+        simulator.press(|_, _| ());
+        let trace = simulator.take_trace();
+        assert_eq!(trace.pulses.len(), 12);
+        assert_eq!(trace.pulses[0].press, 2);
+        simulator.press(|_, _| ());
+        assert_eq!(simulator.take_trace(), Trace::default());
+
+        let module_infos = parse_example::<Day20>(include_str!("examples/day20.txt"));
+        let trace = Day20::trace(&module_infos, 1).unwrap().unwrap();
+        let json = trace.write(TraceFormat::JsonLines);
+        let lines: Vec<&str> = json.lines().collect();
+        assert_eq!(lines.len(), 12);
+        assert_eq!(
+            lines[0],
+            r#"{"press": 1, "position": 0, "source": "button", "target": "broadcaster", "pulse": "low"}"#
+        );
+        assert_eq!(
+            lines[11],
+            r#"{"press": 1, "position": 11, "source": "inv", "target": "a", "pulse": "high"}"#
+        );
+
+        let vcd = trace.write(TraceFormat::Vcd);
+        assert!(vcd.contains("$var integer 64 ! press $end\n$var wire 1 \" a $end\n"));
+        assert!(vcd.contains("$var wire 1 ' inv $end\n"));
+        let changes = "$end
+#1
+b1 !
+#5
+1\"
+#6
+1#
+#7
+1&
+#9
+0\"
+#10
+0#
+#11
+0&
+#12
+1'
+";
+        assert!(vcd.ends_with(changes), "{}", vcd);
+    }
+
+    #[test]
+    fn graph_of_the_network() {
+        let dot = network_graph(&parse_example::<Day20>(include_str!(
+            "examples/day20_2.txt"
+        )))
+        .to_string();
+        assert!(dot.starts_with("digraph {\n  \"button\" [shape=\"plaintext\"];\n"));
+        assert!(dot.contains("  \"a\" [shape=\"box\", label=\"%a\"];\n"));
+        assert!(dot.contains("  \"con\" [shape=\"diamond\", label=\"&con\"];\n"));
+        assert!(dot.contains("  \"output\" [shape=\"ellipse\"];\n"));
+        assert!(dot.contains("  \"button\" -> \"broadcaster\";\n"));
+        assert!(dot.contains("  \"a\" -> \"con\";\n"));
+        assert_eq!(dot.matches(" -> ").count(), 7);
+    }
+
+    #[test]
+    fn first_press_in_small_network() {
+        let module_infos = parse_example::<Day20>(include_str!("examples/day20_2.txt"));
+        for (target, pulse_type) in [
+            ("output", PulseType::Low),
+            ("output", PulseType::High),
+            ("b", PulseType::High),
+            ("con", PulseType::Low),
+        ] {
+            assert_eq!(
+                first_press_receiving(&module_infos, target, pulse_type, 100),
+                brute_force(&module_infos, target, pulse_type),
+                "{} receiving {:?}",
+                target,
+                pulse_type
+            );
+        }
+        assert_eq!(
+            first_press_receiving(&module_infos, "output", PulseType::Low, 100),
+            Some(1)
+        );
+        assert_eq!(
+            first_press_receiving(&module_infos, "a", PulseType::High, 100),
+            None
+        );
+    }
+
+    #[test]
+    fn first_press_in_independent_counters() {
+        // Two counters of 2 and 3 bits that report to a conjunction in front of rx.
+        let module_infos = parse_example::<Day20>(
+            "broadcaster -> a1, b1
+%a1 -> a2, ca
+%a2 -> ca
+&ca -> a1, na
+&na -> hub
+%b1 -> b2, cb
+%b2 -> b3, cb
+%b3 -> cb
+&cb -> b1, nb
+&nb -> hub
+&hub -> rx",
+        );
+        let cycles = split_at_conjunction(&module_infos, "rx", PulseType::Low, 100).unwrap();
+        assert_eq!(cycles.len(), 2);
+        let press = first_press_receiving(&module_infos, "rx", PulseType::Low, 100);
+        // The counters report every 3 and 7 presses.
+        assert_eq!(press, Some(21));
+        assert_eq!(press, brute_force(&module_infos, "rx", PulseType::Low));
+    }
+
+    #[test]
+    fn delayed_inputs() {
+        // The counters from above, with the second one reaching hub through `delay`
+        // more inverters. Its high pulse then arrives after the low pulse of the
+        // first one.
+        for delay in [0, 2, 4, 6, 8] {
+            let mut text = "broadcaster -> a1, b1
+%a1 -> a2, ca
+%a2 -> ca
+&ca -> a1, na
+&na -> hub
+%b1 -> b2, cb
+%b2 -> b3, cb
+%b3 -> cb
+&cb -> b1, nb
+"
+            .to_owned();
+            let mut last = "nb".to_owned();
+            for inverter in 1..=delay {
+                text.push_str(&format!("&{} -> d{}\n", last, inverter));
+                last = format!("d{}", inverter);
+            }
+            text.push_str(&format!("&{} -> hub\n&hub -> rx", last));
+            let module_infos = parse_example::<Day20>(&text);
+            let press = first_press_receiving(&module_infos, "rx", PulseType::Low, 1000);
+            assert_eq!(
+                press,
+                brute_force(&module_infos, "rx", PulseType::Low),
+                "delay {}",
+                delay
+            );
+            if delay == 0 {
+                assert_eq!(press, Some(21));
+            }
+            if delay >= 4 {
+                assert_eq!(press, None);
+            }
+        }
+    }
 
-    crate::AOCResult {
-        day: 20,
-        task: 2,
-        r: result,
+    #[test]
+    fn inputs_that_stay_high() {
+        // c stays high after its high pulse in press 2 until b sends one in press 3,
+        // while no press has high pulses from both.
+        let module_infos = parse_example::<Day20>(
+            "broadcaster -> a, b
+%a -> c
+%c -> hub
+%b -> hub
+&hub -> rx",
+        );
+        assert_eq!(
+            split_at_conjunction(&module_infos, "rx", PulseType::Low, 100),
+            None
+        );
+        let press = first_press_receiving(&module_infos, "rx", PulseType::Low, 100);
+        assert_eq!(press, Some(3));
+        assert_eq!(press, brute_force(&module_infos, "rx", PulseType::Low));
+    }
+
+    #[test]
+    fn first_press_beyond_the_limit() {
+        // A 10 bit counter, whose highest bit turns off in press 1024.
+        let mut text = "broadcaster -> a1\n".to_owned();
+        for bit in 1..10 {
+            text.push_str(&format!("%a{} -> a{}\n", bit, bit + 1));
+        }
+        text.push_str("%a10 -> rx\n");
+        let module_infos = parse_example::<Day20>(&text);
+        assert_eq!(
+            first_press_receiving(&module_infos, "rx", PulseType::Low, 2000),
+            Some(1024)
+        );
+        assert_eq!(
+            first_press_receiving(&module_infos, "rx", PulseType::Low, 1000),
+            None
+        );
     }
 }
@@ -1,196 +1,107 @@
-use std::collections::{BTreeMap, BinaryHeap, HashMap};
-use std::fs::File;
-use std::io::{self, BufRead};
-use std::path::Path;
-
-fn lines_from_file(filename: impl AsRef<Path>) -> io::Result<impl Iterator<Item = String>> {
-    let file = File::open(&filename)?;
-    let reader = io::BufReader::new(file);
-    Ok(reader.lines().map(|l| l.expect("Could not parse line")))
+use std::collections::{BTreeMap, HashMap};
+
+use crate::error::Error;
+use crate::geometry::Pos;
+use crate::grid::Grid;
+use crate::input::InputSource;
+use crate::parse::ParseError;
+use crate::search;
+use crate::solution::{Answer, Solution};
+
+const DAY: u32 = 21;
+
+/// The number of steps the elf takes in part 1 and in part 2.
+const STEPS: [usize; 2] = [64, 26501365];
+
+pub struct Garden {
+    map: Grid<char>,
+    /// The number of steps of each part. Parsing always sets [`STEPS`]; the tests
+    /// change it for the example, which uses fewer.
+    steps: [usize; 2],
 }
 
-pub fn parse(filename: &str) -> Vec<String> {
-    lines_from_file(filename).unwrap().collect()
-}
-
-fn get_starting_pos(input: &mut [String]) -> (usize, usize) {
-    for (row, s) in input.iter_mut().enumerate() {
-        if let Some((col, _)) = s.chars().enumerate().find(|(_, c)| *c == 'S') {
-            unsafe {
-                s.as_bytes_mut()[col] = ".".as_bytes()[0];
-            }
-            return (row, col);
-        }
-    }
-    panic!()
-}
-
-#[derive(Debug, PartialEq, Eq)]
-struct MinDistancePos {
-    pos: (usize, usize),
-    distance: usize,
-}
-
-impl std::cmp::Ord for MinDistancePos {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        other.distance.cmp(&self.distance)
-    }
-}
-
-impl std::cmp::PartialOrd for MinDistancePos {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
-    }
+/// Finds the start tile and turns it into a garden plot.
+fn get_starting_pos(input: &mut Grid<char>) -> Result<(usize, usize), ParseError> {
+    let pos = input
+        .find(|&c| c == 'S')
+        .ok_or_else(|| ParseError::missing("a start tile 'S'").in_day(DAY))?;
+    input[pos] = '.';
+    Ok(pos)
 }
 
+/// The number of steps to every garden plot that is at most `max_steps` away.
 fn shortest_paths_bounded(
-    input: &[String],
+    input: &Grid<char>,
     starting_pos: (usize, usize),
-    max_steps: usize,
+    max_steps: Option<usize>,
 ) -> HashMap<(usize, usize), usize> {
-    let mut shortest_paths = HashMap::new();
-    let mut bheap = BinaryHeap::new();
-    bheap.push(MinDistancePos {
-        pos: starting_pos,
-        distance: 0,
-    });
-    shortest_paths.insert(starting_pos, 0usize);
-    let dot = ".".as_bytes()[0];
-    while let Some(min_pos) = bheap.pop() {
-        if min_pos.distance >= max_steps {
-            continue;
-        }
-        let pos = min_pos.pos;
-        if pos.0 > 0
-            && !shortest_paths.contains_key(&(pos.0 - 1, pos.1))
-            && input[pos.0 - 1].as_bytes()[pos.1] == dot
-        {
-            shortest_paths.insert((pos.0 - 1, pos.1), min_pos.distance + 1);
-            bheap.push(MinDistancePos {
-                pos: (pos.0 - 1, pos.1),
-                distance: min_pos.distance + 1,
-            });
-        }
-        if pos.1 > 0
-            && !shortest_paths.contains_key(&(pos.0, pos.1 - 1))
-            && input[pos.0].as_bytes()[pos.1 - 1] == dot
-        {
-            shortest_paths.insert((pos.0, pos.1 - 1), min_pos.distance + 1);
-            bheap.push(MinDistancePos {
-                pos: (pos.0, pos.1 - 1),
-                distance: min_pos.distance + 1,
-            });
-        }
-        if pos.0 < input.len() - 1
-            && !shortest_paths.contains_key(&(pos.0 + 1, pos.1))
-            && input[pos.0 + 1].as_bytes()[pos.1] == dot
-        {
-            shortest_paths.insert((pos.0 + 1, pos.1), min_pos.distance + 1);
-            bheap.push(MinDistancePos {
-                pos: (pos.0 + 1, pos.1),
-                distance: min_pos.distance + 1,
-            });
-        }
-        if pos.1 < input[0].len() - 1
-            && !shortest_paths.contains_key(&(pos.0, pos.1 + 1))
-            && input[pos.0].as_bytes()[pos.1 + 1] == dot
-        {
-            shortest_paths.insert((pos.0, pos.1 + 1), min_pos.distance + 1);
-            bheap.push(MinDistancePos {
-                pos: (pos.0, pos.1 + 1),
-                distance: min_pos.distance + 1,
-            });
-        }
-    }
-    shortest_paths
+    search::distances(
+        [starting_pos],
+        |&pos| {
+            input
+                .neighbours4(pos)
+                .filter(|&next| input[next] == '.')
+                .map(|next| (next, 1))
+        },
+        max_steps,
+    )
 }
 
 fn shortest_paths(
-    input: &[String],
+    input: &Grid<char>,
     starting_pos: (usize, usize),
 ) -> HashMap<(usize, usize), usize> {
-    let mut shortest_paths = HashMap::new();
-    let mut bheap = BinaryHeap::new();
-    bheap.push(MinDistancePos {
-        pos: starting_pos,
-        distance: 0,
-    });
-    shortest_paths.insert(starting_pos, 0usize);
-    let dot = ".".as_bytes()[0];
-    while let Some(min_pos) = bheap.pop() {
-        let pos = min_pos.pos;
-        if pos.0 > 0
-            && !shortest_paths.contains_key(&(pos.0 - 1, pos.1))
-            && input[pos.0 - 1].as_bytes()[pos.1] == dot
-        {
-            shortest_paths.insert((pos.0 - 1, pos.1), min_pos.distance + 1);
-            bheap.push(MinDistancePos {
-                pos: (pos.0 - 1, pos.1),
-                distance: min_pos.distance + 1,
-            });
-        }
-        if pos.1 > 0
-            && !shortest_paths.contains_key(&(pos.0, pos.1 - 1))
-            && input[pos.0].as_bytes()[pos.1 - 1] == dot
-        {
-            shortest_paths.insert((pos.0, pos.1 - 1), min_pos.distance + 1);
-            bheap.push(MinDistancePos {
-                pos: (pos.0, pos.1 - 1),
-                distance: min_pos.distance + 1,
-            });
-        }
-        if pos.0 < input.len() - 1
-            && !shortest_paths.contains_key(&(pos.0 + 1, pos.1))
-            && input[pos.0 + 1].as_bytes()[pos.1] == dot
-        {
-            shortest_paths.insert((pos.0 + 1, pos.1), min_pos.distance + 1);
-            bheap.push(MinDistancePos {
-                pos: (pos.0 + 1, pos.1),
-                distance: min_pos.distance + 1,
-            });
-        }
-        if pos.1 < input[0].len() - 1
-            && !shortest_paths.contains_key(&(pos.0, pos.1 + 1))
-            && input[pos.0].as_bytes()[pos.1 + 1] == dot
-        {
-            shortest_paths.insert((pos.0, pos.1 + 1), min_pos.distance + 1);
-            bheap.push(MinDistancePos {
-                pos: (pos.0, pos.1 + 1),
-                distance: min_pos.distance + 1,
-            });
-        }
-    }
-    shortest_paths
+    shortest_paths_bounded(input, starting_pos, None)
 }
 
-pub fn task1() -> crate::AOCResult<usize> {
-    let mut input = parse("src/day21.txt");
-    let starting_pos = get_starting_pos(&mut input);
-    let shortes_paths = shortest_paths_bounded(&input, starting_pos, 64);
-    let r = shortes_paths
-        .iter()
-        .filter(|(pos, _)| {
-            ((pos.0.max(starting_pos.0) - pos.0.min(starting_pos.0))
-                + (pos.1.max(starting_pos.1) - pos.1.min(starting_pos.1)))
-                % 2
-                == 64 % 2
+pub struct Day21;
+
+impl Solution for Day21 {
+    const DAY: u32 = DAY;
+
+    type Input = Garden;
+
+    fn parse(input: &InputSource) -> Result<Self::Input, Error> {
+        Ok(Garden {
+            map: Grid::parse_chars(DAY, &input.grid()?, ".#S")?,
+            steps: STEPS,
         })
-        .count();
+    }
+
+    fn part1(garden: &Self::Input) -> Result<Answer, Error> {
+        let steps = garden.steps[0];
+        let mut input = garden.map.clone();
+        let starting_pos = get_starting_pos(&mut input)?;
+        let shortes_paths = shortest_paths_bounded(&input, starting_pos, Some(steps));
+        let r = shortes_paths
+            .iter()
+            .filter(|(&pos, _)| {
+                Pos::from(pos).manhattan_distance(starting_pos.into()) % 2 == steps % 2
+            })
+            .count();
+
+        Ok(Answer::from(r))
+    }
 
-    crate::AOCResult {
-        day: 21,
-        task: 1,
-        r,
+    fn part2(garden: &Self::Input) -> Result<Answer, Error> {
+        let steps = garden.steps[1];
+
+        let mut input = garden.map.clone();
+        // Remove middle S;
+        get_starting_pos(&mut input)?;
+        let r = calculate_steps_large(&input, steps);
+
+        Ok(Answer::from(r))
     }
 }
 
-fn _print_distances(input: &[String], distances: &HashMap<(usize, usize), usize>) {
+fn _print_distances(input: &Grid<char>, distances: &HashMap<(usize, usize), usize>) {
     use std::fmt::Write;
     let mut s = String::new();
-    for (row, line) in input.iter().enumerate() {
-        for (col, char) in line.chars().enumerate() {
+    for (row, line) in input.rows().enumerate() {
+        for (col, char) in line.iter().enumerate() {
             s.push(' ');
-            if char == '.' {
+            if *char == '.' {
                 if let Some(distance) = distances.get(&(row, col)) {
                     write!(&mut s, "{:3}", distance).unwrap();
                 } else {
@@ -236,7 +147,7 @@ struct Number {
 }
 
 fn numbers(num_full_size: usize, num_steps: usize) -> Number {
-    match (num_full_size % 2 == 0, num_steps % 2 == 0) {
+    match (num_full_size.is_multiple_of(2), num_steps.is_multiple_of(2)) {
         (true, true) => {
             let diagonal_lines = num_full_size - 2;
             let h = diagonal_lines / 2;
@@ -290,7 +201,7 @@ fn get_max_(map: &BTreeMap<usize, usize>) -> (usize, usize) {
     }
 }
 
-fn calculate_steps_large(input: &Vec<String>, num_steps: usize) -> usize {
+fn calculate_steps_large(input: &Grid<char>, num_steps: usize) -> usize {
     /*
      * Assumptions for input:
      *  1. Start is directly in center
@@ -299,11 +210,11 @@ fn calculate_steps_large(input: &Vec<String>, num_steps: usize) -> usize {
      *  4. The input is a square
      *  5. The size is odd
      */
-    let len = input.len();
+    let len = input.height();
     let mid = len / 2;
     let mut count = 0;
 
-    // TODO: Note that there are some errors when remaining top == input.len();
+    // TODO: Note that there are some errors when remaining top == input.height();
     let num_full_size = num_steps / len;
     let remaining_top = num_steps - num_full_size * len + mid;
     let remaining_top_corner = num_steps - num_full_size * len - 1;
@@ -408,7 +319,7 @@ fn calculate_steps_large(input: &Vec<String>, num_steps: usize) -> usize {
     // Center Tile
     let distance_from_center = transform_to_distance_map(shortest_paths(input, (mid, mid)));
     let (odd_cnt, even_cnt) = get_max_(&distance_from_center);
-    count += if num_steps % 2 == 0 {
+    count += if num_steps.is_multiple_of(2) {
         even_cnt
     } else {
         odd_cnt
@@ -417,17 +328,21 @@ fn calculate_steps_large(input: &Vec<String>, num_steps: usize) -> usize {
     count
 }
 
-pub fn task2() -> crate::AOCResult<usize> {
-    let steps = 26501365;
-
-    let mut input = parse("src/day21.txt");
-    // Remove middle S;
-    get_starting_pos(&mut input);
-    let r = calculate_steps_large(&input, steps);
-
-    crate::AOCResult {
-        day: 21,
-        task: 2,
-        r,
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::solution::example_with;
+
+    // Part 2 relies on the shape of the real input (a clear starting row and
+    // column and a square map), which the example does not have.
+
+    #[test]
+    fn example_part1() {
+        assert_eq!(
+            example_with::<Day21>(include_str!("examples/day21.txt"), 1, |garden| {
+                garden.steps[0] = 6
+            }),
+            Answer(16)
+        );
     }
 }
@@ -1,21 +1,21 @@
 use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
-use std::fs::File;
-use std::io::{self, BufRead};
-use std::path::Path;
-
-fn lines_from_file(filename: impl AsRef<Path>) -> io::Result<impl Iterator<Item = String>> {
-    let file = File::open(&filename)?;
-    let reader = io::BufReader::new(file);
-    Ok(reader.lines().map(|l| l.expect("Could not parse line")))
-}
+
+use crate::error::Error;
+use crate::input::InputSource;
+use crate::parse::{self, ParseError};
+use crate::solution::{Answer, Solution};
+
+const DAY: u32 = 22;
 
 #[derive(Debug, PartialEq, Eq, Clone)]
-struct Brick {
+pub struct Brick {
     id: usize,
-    start: (usize, usize, usize),
-    end: (usize, usize, usize),
+    start: Coordinate,
+    end: Coordinate,
 }
 
+type Coordinate = (usize, usize, usize);
+
 impl Brick {
     fn overlaps_x_y(&self, other: &Brick) -> bool {
         self.start.0 <= other.end.0
@@ -25,29 +25,18 @@ impl Brick {
     }
 }
 
-fn parse() -> Vec<Brick> {
-    lines_from_file("src/day22.txt")
-        .unwrap()
-        .enumerate()
-        .map(|(index, line)| parse_brick(&line, index))
-        .collect()
+fn parse_brick(line: &str) -> Result<(Coordinate, Coordinate), ParseError> {
+    let (start, end) = parse::split_once(line, "~")?;
+    Ok((parse_coordinate(start)?, parse_coordinate(end)?))
 }
 
-fn parse_brick(line: &str, id: usize) -> Brick {
-    let mut iter = line.split('~');
-    let mut iter2 = iter.next().unwrap().split(',');
-    let start = (
-        iter2.next().unwrap().parse::<usize>().unwrap(),
-        iter2.next().unwrap().parse::<usize>().unwrap(),
-        iter2.next().unwrap().parse::<usize>().unwrap(),
-    );
-    let mut iter2 = iter.next().unwrap().split(',');
-    let end = (
-        iter2.next().unwrap().parse::<usize>().unwrap(),
-        iter2.next().unwrap().parse::<usize>().unwrap(),
-        iter2.next().unwrap().parse::<usize>().unwrap(),
-    );
-    Brick { id, start, end }
+fn parse_coordinate(s: &str) -> Result<Coordinate, ParseError> {
+    let mut iter = s.split(',');
+    Ok((
+        parse::number(parse::next(&mut iter, "an x coordinate")?)?,
+        parse::number(parse::next(&mut iter, "a y coordinate")?)?,
+        parse::number(parse::next(&mut iter, "a z coordinate")?)?,
+    ))
 }
 
 fn settle_bricks(snapshot: &Vec<Brick>) -> BTreeMap<usize, Vec<Brick>> {
@@ -147,26 +136,54 @@ fn calculate_disintigratable_bricks(
     cnt
 }
 
-pub fn task1() -> crate::AOCResult<usize> {
-    let mut bricks = parse();
-    bricks.sort_by(|l, r| {
-        l.start
-            .2
-            .cmp(&r.start.2)
-            .then_with(|| l.start.0.cmp(&r.start.0))
-            .then_with(|| l.start.1.cmp(&r.start.1))
-    });
-    let stack = settle_bricks(&bricks);
-    let helper_maps = calculate_supporting_layers(&stack);
-    let r = calculate_disintigratable_bricks(
-        &helper_maps.layer_block_end_map,
-        &helper_maps.supports_bricks_map,
-    );
-
-    crate::AOCResult {
-        day: 22,
-        task: 1,
-        r,
+pub struct Day22;
+
+impl Solution for Day22 {
+    const DAY: u32 = DAY;
+
+    type Input = Vec<Brick>;
+
+    fn parse(input: &InputSource) -> Result<Self::Input, Error> {
+        Ok(parse::lines(DAY, 1, &input.lines()?, parse_brick)?
+            .into_iter()
+            .enumerate()
+            .map(|(id, (start, end))| Brick { id, start, end })
+            .collect())
+    }
+
+    fn part1(input: &Self::Input) -> Result<Answer, Error> {
+        let mut bricks = input.clone();
+        bricks.sort_by(|l, r| {
+            l.start
+                .2
+                .cmp(&r.start.2)
+                .then_with(|| l.start.0.cmp(&r.start.0))
+                .then_with(|| l.start.1.cmp(&r.start.1))
+        });
+        let stack = settle_bricks(&bricks);
+        let helper_maps = calculate_supporting_layers(&stack);
+        let r = calculate_disintigratable_bricks(
+            &helper_maps.layer_block_end_map,
+            &helper_maps.supports_bricks_map,
+        );
+
+        Ok(Answer::from(r))
+    }
+
+    fn part2(input: &Self::Input) -> Result<Answer, Error> {
+        let mut bricks = input.clone();
+        bricks.sort_by(|l, r| {
+            l.start
+                .2
+                .cmp(&r.start.2)
+                .then_with(|| l.start.0.cmp(&r.start.0))
+                .then_with(|| l.start.1.cmp(&r.start.1))
+        });
+        let stack = settle_bricks(&bricks);
+        let m = calculate_disintigration_falling_map(&stack);
+        let r: usize = m.values().sum();
+
+        Ok(Answer::from(r))
     }
 }
 
@@ -266,22 +283,24 @@ fn calculate_disintigration_falling_map(
     disintigrate_falling_map
 }
 
-pub fn task2() -> crate::AOCResult<usize> {
-    let mut bricks = parse();
-    bricks.sort_by(|l, r| {
-        l.start
-            .2
-            .cmp(&r.start.2)
-            .then_with(|| l.start.0.cmp(&r.start.0))
-            .then_with(|| l.start.1.cmp(&r.start.1))
-    });
-    let stack = settle_bricks(&bricks);
-    let m = calculate_disintigration_falling_map(&stack);
-    let r = m.values().sum();
-
-    crate::AOCResult {
-        day: 22,
-        task: 2,
-        r,
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::solution::example;
+
+    #[test]
+    fn example_part1() {
+        assert_eq!(
+            example::<Day22>(include_str!("examples/day22.txt"), 1),
+            Answer(5)
+        );
+    }
+
+    #[test]
+    fn example_part2() {
+        assert_eq!(
+            example::<Day22>(include_str!("examples/day22.txt"), 2),
+            Answer(7)
+        );
     }
 }
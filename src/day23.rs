@@ -1,57 +1,34 @@
 use std::collections::{HashMap, HashSet};
-use std::fs::File;
-use std::io::{self, BufRead};
-use std::path::Path;
-
-fn lines_from_file(filename: impl AsRef<Path>) -> io::Result<impl Iterator<Item = String>> {
-    let file = File::open(&filename)?;
-    let reader = io::BufReader::new(file);
-    Ok(reader.lines().map(|l| l.expect("Could not parse line")))
-}
 
-fn parse() -> Vec<String> {
-    lines_from_file("src/day23.txt").unwrap().collect()
-}
+use crate::dot::Dot;
+use crate::error::Error;
+use crate::grid::Grid;
+use crate::input::InputSource;
+use crate::solution::{Answer, Solution};
 
-const H: u8 = 35;
-const E: u8 = 46;
-const D: u8 = 118;
-const R: u8 = 62;
+const DAY: u32 = 23;
 
-fn i(input: &[String], row: usize, col: usize) -> u8 {
-    input[row].as_bytes()[col]
-}
+const H: char = '#';
+const E: char = '.';
+const D: char = 'v';
+const R: char = '>';
 
-fn find_intersections(input: &[String]) -> HashSet<(usize, usize)> {
+fn find_intersections(input: &Grid<char>) -> HashSet<(usize, usize)> {
     let mut intersections = HashSet::new();
-    for (row, s) in input.iter().enumerate() {
-        for (col, s) in s.as_bytes().iter().enumerate() {
-            if *s == H {
-                continue;
-            }
-            let mut cnt = 0;
-            if row > 0 && i(input, row - 1, col) != H {
-                cnt += 1;
-            }
-            if col < input[0].len() - 1 && i(input, row, col + 1) != H {
-                cnt += 1;
-            }
-            if row < input.len() - 1 && i(input, row + 1, col) != H {
-                cnt += 1;
-            }
-            if col > 0 && i(input, row, col - 1) != H {
-                cnt += 1;
-            }
-            if cnt != 2 {
-                intersections.insert((row, col));
-            }
+    for (pos, s) in input.iter() {
+        if *s == H {
+            continue;
+        }
+        let cnt = input.neighbours4(pos).filter(|&n| input[n] != H).count();
+        if cnt != 2 {
+            intersections.insert(pos);
         }
     }
     intersections
 }
 
 fn follow_path(
-    input: &[String],
+    input: &Grid<char>,
     intersections: &HashSet<(usize, usize)>,
     start: (usize, usize),
     mut last: (usize, usize),
@@ -61,29 +38,29 @@ fn follow_path(
     while !intersections.contains(&cur) {
         if cur.0 > 0
             && (cur.0 - 1, cur.1) != last
-            && i(input, cur.0 - 1, cur.1) != H
-            && i(input, cur.0, cur.1) == E
+            && input[(cur.0 - 1, cur.1)] != H
+            && input[(cur.0, cur.1)] == E
         {
             last = cur;
             cur = (cur.0 - 1, cur.1);
-        } else if cur.1 < input[0].len() - 1
+        } else if cur.1 < input.width() - 1
             && last != (cur.0, cur.1 + 1)
-            && i(input, cur.0, cur.1 + 1) != H
-            && (i(input, cur.0, cur.1) == E || i(input, cur.0, cur.1) == R)
+            && input[(cur.0, cur.1 + 1)] != H
+            && (input[(cur.0, cur.1)] == E || input[(cur.0, cur.1)] == R)
         {
             last = cur;
             cur = (cur.0, cur.1 + 1);
-        } else if cur.0 < input.len() - 1
+        } else if cur.0 < input.height() - 1
             && last != (cur.0 + 1, cur.1)
-            && i(input, cur.0 + 1, cur.1) != H
-            && (i(input, cur.0, cur.1) == E || i(input, cur.0, cur.1) == D)
+            && input[(cur.0 + 1, cur.1)] != H
+            && (input[(cur.0, cur.1)] == E || input[(cur.0, cur.1)] == D)
         {
             last = cur;
             cur = (cur.0 + 1, cur.1);
         } else if cur.1 > 0
             && last != (cur.0, cur.1 - 1)
-            && i(input, cur.0, cur.1 - 1) != H
-            && i(input, cur.0, cur.1) == E
+            && input[(cur.0, cur.1 - 1)] != H
+            && input[(cur.0, cur.1)] == E
         {
             last = cur;
             cur = (cur.0, cur.1 - 1);
@@ -99,15 +76,15 @@ type SuccessorMap = HashMap<(usize, usize), HashSet<(usize, usize)>>;
 type InteresectionStepMap = HashMap<((usize, usize), (usize, usize)), usize>;
 
 fn steps_between_intersections(
-    input: &[String],
+    input: &Grid<char>,
     intersections: &HashSet<(usize, usize)>,
 ) -> (SuccessorMap, InteresectionStepMap) {
     let mut successors = HashMap::new();
     let mut steps_between_intersections = HashMap::new();
     for intersection in intersections {
         if intersection.0 > 0
-            && i(input, intersection.0 - 1, intersection.1) != H
-            && i(input, intersection.0, intersection.1) == E
+            && input[(intersection.0 - 1, intersection.1)] != H
+            && input[(intersection.0, intersection.1)] == E
         {
             if let Some(path) = follow_path(
                 input,
@@ -122,10 +99,10 @@ fn steps_between_intersections(
                 steps_between_intersections.insert((*intersection, path.0), path.1 + 1);
             }
         }
-        if intersection.1 < input[0].len() - 1
-            && i(input, intersection.0, intersection.1 + 1) != H
-            && (i(input, intersection.0, intersection.1) == E
-                || i(input, intersection.0, intersection.1) == R)
+        if intersection.1 < input.width() - 1
+            && input[(intersection.0, intersection.1 + 1)] != H
+            && (input[(intersection.0, intersection.1)] == E
+                || input[(intersection.0, intersection.1)] == R)
         {
             if let Some(path) = follow_path(
                 input,
@@ -140,10 +117,10 @@ fn steps_between_intersections(
                 steps_between_intersections.insert((*intersection, path.0), path.1 + 1);
             }
         }
-        if intersection.0 < input.len() - 1
-            && i(input, intersection.0 + 1, intersection.1) != H
-            && (i(input, intersection.0, intersection.1) == E
-                || i(input, intersection.0, intersection.1) == D)
+        if intersection.0 < input.height() - 1
+            && input[(intersection.0 + 1, intersection.1)] != H
+            && (input[(intersection.0, intersection.1)] == E
+                || input[(intersection.0, intersection.1)] == D)
         {
             if let Some(path) = follow_path(
                 input,
@@ -159,8 +136,8 @@ fn steps_between_intersections(
             }
         }
         if intersection.1 > 0
-            && i(input, intersection.0, intersection.1 - 1) != H
-            && i(input, intersection.0, intersection.1) == E
+            && input[(intersection.0, intersection.1 - 1)] != H
+            && input[(intersection.0, intersection.1)] == E
         {
             if let Some(path) = follow_path(
                 input,
@@ -221,16 +198,20 @@ fn map_to_u64(
     )
 }
 
-fn find_all_paths(input: &[String]) -> usize {
+fn find_all_paths(input: &Grid<char>) -> usize {
     let intersections = find_intersections(input);
-    let final_col = input[input.len() - 1].find('.').unwrap();
+    let final_col = input
+        .row(input.height() - 1)
+        .iter()
+        .position(|&c| c == E)
+        .unwrap();
     let (successors, steps_between_intersections) =
         steps_between_intersections(input, &intersections);
 
     let (s_m, i_s_m) = map_to_u64(
         &successors,
         &steps_between_intersections,
-        (input.len() - 1, final_col),
+        (input.height() - 1, final_col),
     );
 
     let start_seen = 1u64;
@@ -256,27 +237,99 @@ fn find_all_paths(input: &[String]) -> usize {
     max_steps
 }
 
-pub fn task1() -> crate::AOCResult<usize> {
-    let input = parse();
-    let r = find_all_paths(&input);
+/// The intersections of the trails, with an edge labelled with the number of steps
+/// to every intersection that can be reached from there without passing another
+/// one. Slopes can only be walked downhill, so the edges have a direction.
+fn intersection_graph(input: &Grid<char>) -> Dot {
+    let intersections = find_intersections(input);
+    let (successors, steps) = steps_between_intersections(input, &intersections);
+    let id = |(row, col): (usize, usize)| format!("{},{}", row, col);
 
-    crate::AOCResult {
-        day: 23,
-        task: 1,
-        r,
+    let mut dot = Dot::digraph();
+    dot.node_defaults(&[("shape", "box")]);
+    let mut nodes: Vec<_> = intersections.into_iter().collect();
+    nodes.sort();
+    for &node in &nodes {
+        if node.0 == 0 {
+            dot.node(&id(node), &[("shape", "doublecircle"), ("label", "start")]);
+        } else if node.0 == input.height() - 1 {
+            dot.node(&id(node), &[("shape", "doublecircle"), ("label", "end")]);
+        } else {
+            dot.node(&id(node), &[]);
+        }
     }
+    for &node in &nodes {
+        let Some(succs) = successors.get(&node) else {
+            continue;
+        };
+        let mut succs: Vec<_> = succs.iter().copied().collect();
+        succs.sort();
+        for succ in succs {
+            let label = steps[&(node, succ)].to_string();
+            dot.edge(&id(node), &id(succ), &[("label", &label)]);
+        }
+    }
+    dot
 }
 
-pub fn task2() -> crate::AOCResult<usize> {
-    let mut input = parse();
-    input.iter_mut().for_each(|s| {
-        *s = s.replace(['>', 'v'], ".");
-    });
-    let r = find_all_paths(&input);
-
-    crate::AOCResult {
-        day: 23,
-        task: 2,
-        r,
+pub struct Day23;
+
+impl Solution for Day23 {
+    const DAY: u32 = DAY;
+
+    type Input = Grid<char>;
+
+    fn parse(input: &InputSource) -> Result<Self::Input, Error> {
+        Ok(Grid::parse_chars(DAY, &input.grid()?, ".#<>^v")?)
+    }
+
+    fn part1(input: &Self::Input) -> Result<Answer, Error> {
+        let r = find_all_paths(input);
+
+        Ok(Answer::from(r))
+    }
+
+    fn part2(input: &Self::Input) -> Result<Answer, Error> {
+        let input = input.map(|&c| if c == R || c == D { E } else { c });
+        let r = find_all_paths(&input);
+
+        Ok(Answer::from(r))
+    }
+
+    fn graph(input: &Self::Input) -> Result<Option<Dot>, Error> {
+        Ok(Some(intersection_graph(input)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::solution::{example, parse_example};
+
+    #[test]
+    fn example_part1() {
+        assert_eq!(
+            example::<Day23>(include_str!("examples/day23.txt"), 1),
+            Answer(94)
+        );
+    }
+
+    #[test]
+    fn example_part2() {
+        assert_eq!(
+            example::<Day23>(include_str!("examples/day23.txt"), 2),
+            Answer(154)
+        );
+    }
+
+    #[test]
+    fn graph_of_the_intersections() {
+        let input = parse_example::<Day23>(include_str!("examples/day23.txt"));
+        let dot = intersection_graph(&input).to_string();
+        assert!(dot.starts_with("digraph {\n  node [shape=\"box\"];\n"));
+        assert!(dot.contains("  \"0,1\" [shape=\"doublecircle\", label=\"start\"];\n"));
+        assert!(dot.contains("  \"22,21\" [shape=\"doublecircle\", label=\"end\"];\n"));
+        assert!(dot.contains("  \"0,1\" -> \"5,3\" [label=\"15\"];\n"));
+        assert!(dot.contains("  \"19,19\" -> \"22,21\" [label=\"5\"];\n"));
     }
 }
@@ -1,213 +1,469 @@
-use std::fs::File;
-use std::io::{self, BufRead};
-use std::path::Path;
+use std::fmt;
+use std::ops::{Add, Mul, Sub};
 
-fn lines_from_file(filename: impl AsRef<Path>) -> io::Result<impl Iterator<Item = String>> {
-    let file = File::open(&filename)?;
-    let reader = io::BufReader::new(file);
-    Ok(reader.lines().map(|l| l.expect("Could not parse line")))
-}
+use crate::error::Error;
+use crate::input::InputSource;
+use crate::parse::{self, ParseError};
+use crate::solution::{Answer, Solution};
 
-#[derive(Debug)]
-struct Vector {
-    x: f64,
-    y: f64,
-    z: f64,
-}
+const DAY: u32 = 24;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 struct Vectori128 {
     x: i128,
     y: i128,
     z: i128,
 }
 
-impl From<&Vector> for Vectori128 {
-    fn from(value: &Vector) -> Self {
+impl Vectori128 {
+    fn cross(self, other: Vectori128) -> Vectori128 {
         Vectori128 {
-            x: value.x.floor() as i128,
-            y: value.y.floor() as i128,
-            z: value.z.floor() as i128,
+            x: self.y * other.z - self.z * other.y,
+            y: self.z * other.x - self.x * other.z,
+            z: self.x * other.y - self.y * other.x,
         }
     }
+
+    fn is_zero(self) -> bool {
+        self == Vectori128 { x: 0, y: 0, z: 0 }
+    }
+
+    /// Divides every coordinate by `divisor`, if they are all multiples of it.
+    fn div_exact(self, divisor: i128) -> Option<Vectori128> {
+        if divisor == 0 || self.x % divisor != 0 || self.y % divisor != 0 || self.z % divisor != 0 {
+            return None;
+        }
+        Some(Vectori128 {
+            x: self.x / divisor,
+            y: self.y / divisor,
+            z: self.z / divisor,
+        })
+    }
+
+    /// The shortest vector with integer coordinates in the same direction, `None`
+    /// for the zero vector.
+    fn reduced(self) -> Option<Vectori128> {
+        let gcd = gcd(gcd(self.x, self.y), self.z);
+        self.div_exact(gcd)
+    }
 }
 
-struct Hailstone {
-    start: Vector,
-    speed: Vector,
+fn gcd(a: i128, b: i128) -> i128 {
+    if b == 0 {
+        a.abs()
+    } else {
+        gcd(b, a % b)
+    }
 }
 
-impl Hailstone {
-    fn intersects_x_y_at(&self, other: &Self) -> Option<(f64, f64)> {
-        let ss = &self.speed;
-        let os = &other.speed;
-        if ss.x / os.x == ss.y / os.y {
-            return None;
+impl Add for Vectori128 {
+    type Output = Vectori128;
+
+    fn add(self, other: Vectori128) -> Vectori128 {
+        Vectori128 {
+            x: self.x + other.x,
+            y: self.y + other.y,
+            z: self.z + other.z,
         }
-        let cx = self.start.x - other.start.x;
-        let cy = self.start.y - other.start.y;
-        let f_y = ss.y / ss.x;
-        let c_0_1 = os.y - (f_y * os.x);
-        let cy = cy - (f_y * cx);
-        let b = cy / c_0_1;
-        let a = (cx - (b * os.x)) / (-ss.x);
-        // Corssed in the past for one of the hailstones
-        if a < 0f64 || b < 0f64 {
-            return None;
+    }
+}
+
+impl Sub for Vectori128 {
+    type Output = Vectori128;
+
+    fn sub(self, other: Vectori128) -> Vectori128 {
+        Vectori128 {
+            x: self.x - other.x,
+            y: self.y - other.y,
+            z: self.z - other.z,
+        }
+    }
+}
+
+impl Mul<i128> for Vectori128 {
+    type Output = Vectori128;
+
+    fn mul(self, factor: i128) -> Vectori128 {
+        Vectori128 {
+            x: self.x * factor,
+            y: self.y * factor,
+            z: self.z * factor,
         }
-        // println!("a: {}, b: {}", a, b);
-        // if a == b {
-        Some((other.start.x + b * os.x, other.start.y + b * os.y))
-        // }
-        // return None;
     }
 }
 
-fn parse() -> Vec<Hailstone> {
-    lines_from_file("src/day24.txt")
-        .unwrap()
-        .map(|line| parse_line(&line))
-        .collect()
+pub struct Hailstone {
+    start: Vectori128,
+    speed: Vectori128,
+}
+
+/// An exact fraction with a positive denominator.
+#[derive(Debug, Clone, Copy)]
+struct Rational {
+    num: i128,
+    den: i128,
+}
+
+impl Rational {
+    fn new(num: i128, den: i128) -> Rational {
+        assert!(den != 0, "a fraction needs a denominator other than 0");
+        let sign = den.signum();
+        Rational {
+            num: num * sign,
+            den: den * sign,
+        }
+    }
+
+    fn is_negative(self) -> bool {
+        self.num < 0
+    }
+
+    fn is_within(self, (min, max): (i128, i128)) -> bool {
+        min * self.den <= self.num && self.num <= max * self.den
+    }
+}
+
+impl fmt::Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Only for reading, the comparisons stay exact.
+        write!(f, "{:.3}", self.num as f64 / self.den as f64)
+    }
 }
 
-fn parse_line(line: &str) -> Hailstone {
-    let mut iter = line.split('@');
-    Hailstone {
-        start: parse_vec(iter.next().unwrap()),
-        speed: parse_vec(iter.next().unwrap()),
+impl PartialEq for Rational {
+    fn eq(&self, other: &Rational) -> bool {
+        self.num * other.den == other.num * self.den
+    }
+}
+
+impl Eq for Rational {}
+
+/// How the paths of two hailstones cross when looking only at x and y.
+#[derive(Debug, PartialEq, Eq)]
+enum Crossing {
+    /// The paths never meet.
+    Parallel,
+    /// Both hailstones move along the same line.
+    Coincident,
+    /// The paths cross at a point one of the hailstones has already passed.
+    Past,
+    /// Both hailstones will pass the point where their paths cross.
+    Future { x: Rational, y: Rational },
+}
+
+/// The z coordinate of the cross product of two vectors in the x-y plane.
+fn cross_x_y(a: Vectori128, b: Vectori128) -> i128 {
+    a.x * b.y - a.y * b.x
+}
+
+impl Hailstone {
+    /// Where the paths of two hailstones cross in the x-y plane, without rounding.
+    fn crossing_x_y(&self, other: &Self) -> Crossing {
+        // self.start + t * self.speed = other.start + u * other.speed, solved with
+        // Cramer's rule.
+        let offset = other.start - self.start;
+        let det = cross_x_y(self.speed, other.speed);
+        if det == 0 {
+            return if cross_x_y(offset, self.speed) == 0 {
+                Crossing::Coincident
+            } else {
+                Crossing::Parallel
+            };
+        }
+        let t = Rational::new(cross_x_y(offset, other.speed), det);
+        let u = Rational::new(cross_x_y(offset, self.speed), det);
+        if t.is_negative() || u.is_negative() {
+            return Crossing::Past;
+        }
+        Crossing::Future {
+            x: Rational::new(self.start.x * t.den + self.speed.x * t.num, t.den),
+            y: Rational::new(self.start.y * t.den + self.speed.y * t.num, t.den),
+        }
     }
 }
 
-fn parse_vec(s: &str) -> Vector {
+fn parse_line(line: &str) -> Result<Hailstone, ParseError> {
+    let (start, speed) = parse::split_once(line, "@")?;
+    Ok(Hailstone {
+        start: parse_vec(start)?,
+        speed: parse_vec(speed)?,
+    })
+}
+
+fn parse_vec(s: &str) -> Result<Vectori128, ParseError> {
     let mut iter = s.split(',');
-    let x = iter.next().unwrap().trim().parse().unwrap();
-    let y = iter.next().unwrap().trim().parse().unwrap();
-    let z = iter.next().unwrap().trim().parse().unwrap();
-    Vector { x, y, z }
+    let x = parse::number(parse::next(&mut iter, "an x coordinate")?)?;
+    let y = parse::number(parse::next(&mut iter, "a y coordinate")?)?;
+    let z = parse::number(parse::next(&mut iter, "a z coordinate")?)?;
+    Ok(Vectori128 { x, y, z })
 }
 
-fn collisions_x_y(hailstones: &[Hailstone]) -> impl '_ + Iterator<Item = (f64, f64)> {
-    hailstones
-        .iter()
-        .enumerate()
-        .flat_map(|(i, l)| {
-            hailstones
-                .iter()
-                .skip(i + 1)
-                .map(|r| l.intersects_x_y_at(r))
-        })
-        .flatten()
+/// The square of the x-y plane, as its lowest and highest coordinate, in which the
+/// puzzle counts the collisions.
+const TEST_AREA: (i128, i128) = (200000000000000, 400000000000000);
+
+pub struct Hailstorm {
+    hailstones: Vec<Hailstone>,
+    /// The test area. Parsing always sets [`TEST_AREA`]; the tests change it for
+    /// the example, which uses a smaller one.
+    area: (i128, i128),
 }
 
-fn count_collisions_in_area<A: Iterator<Item = (f64, f64)>>(
-    collisions: A,
-    area: (f64, f64),
-) -> usize {
-    collisions
-        .filter(|i| area.0 <= i.0 && i.0 <= area.1 && area.0 <= i.1 && i.1 <= area.1)
-        .count()
+/// What happens to the paths of the hailstones on lines `first` and `second`.
+#[derive(Debug, PartialEq, Eq)]
+struct PairReport {
+    first: usize,
+    second: usize,
+    crossing: Crossing,
+    inside: bool,
 }
 
-fn help(hailstones: &[Hailstone]) {
-    // Use ints for higher precision. Doubles are not enough...
-    let h1 = &hailstones[0];
-    let p1 = Vectori128::from(&h1.start);
-    let d1 = Vectori128::from(&h1.speed);
-    let h2 = &hailstones[1];
-    let p2 = Vectori128::from(&h2.start);
-    let d2 = Vectori128::from(&h2.speed);
-    let h3 = &hailstones[2];
-    let p3 = Vectori128::from(&h3.start);
-    let d3 = Vectori128::from(&h3.speed);
-
-    /*
-     * See https://stackoverflow.com/questions/563198/how-do-you-detect-where-two-line-segments-intersect
-     * v x w is the cross product.
-     * We are in 3 dimensional space.
-     * We want for all i
-     *
-     *      p + t * d = p_i + t * d_i
-     * <=> (p - p_i) = t * (d_i - d)
-     * <=> (p - p_i) = -t * (d * d_i)
-     *
-     * => (p - p_i) x -t * (d - d_i)
-     *
-     * Since -t is a scalar (p - p_i) and (d - d_i) are parallel to each other. Means we can essentially ignore t
-     *
-     * => (p - p_i) x (d - d_i) = 0
-     *
-     * Now, taking two i, for example i = 0 and 1, we can equate:
-     * 
-     * (p - p_1) x (d - d_1) = (p - p_2) x (d - d_2)
-     *
-     * This can be tediously resolved to a linear equation of the form A * x = b, where
-     * x := (p_x, p_y, p_z, d_x, d_y, d_z)^T
-     *
-     * The matrix A and vector b are defined below. Note that each pair of indices gives us 3 equations, for 6 unknowns.
-     * Therefore we need 2 pairs of points to solve this equation.
-     *
-     * The way the matrix is defined, the odd indices of A and the even indices of A form the equations for a set of index pairs.
-     */
-    #[rustfmt::skip]
-    let _A = [
-        [-d1.y + d2.y,  d1.x - d2.x,       0i128, p1.y - p2.y, -p1.x + p2.x,        0i128],
-        [-d1.y + d3.y,  d1.x - d3.x,       0i128, p1.y - p3.y, -p1.x + p3.x,        0i128],
-        [       0i128, -d1.z + d2.z, d1.y - d2.y,       0i128,  p1.z - p2.z, -p1.y + p2.y],
-        [       0i128, -d1.z + d3.z, d1.y - d3.y,       0i128,  p1.z - p3.z, -p1.y + p3.y],
-        [-d1.z + d2.z,        0i128, d1.x - d2.x, p1.z - p2.z,        0i128, -p1.x + p2.x],
-        [-d1.z + d3.z,        0i128, d1.x - d3.x, p1.z - p3.z,        0i128, -p1.x + p3.x],
-    ];
-
-    #[rustfmt::skip]
-    let _b = [
-        p1.y * d1.x - p2.y * d2.x - p1.x * d1.y + p2.x * d2.y,
-        p1.y * d1.x - p3.y * d3.x - p1.x * d1.y + p3.x * d3.y,
-        p1.z * d1.y - p2.z * d2.y - p1.y * d1.z + p2.y * d2.z,
-        p1.z * d1.y - p3.z * d3.y - p1.y * d1.z + p3.y * d3.z,
-        p1.z * d1.x - p2.z * d2.x - p1.x * d1.z + p2.x * d2.z,
-        p1.z * d1.x - p3.z * d3.x - p1.x * d1.z + p3.x * d3.z,
-    ];
-
-    /*/
-        print!("[");
-        for row in A {
-            println!("");
-            for e in row {
-                print!("{}, ", e);
+impl fmt::Display for PairReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Hailstones {} and {}: ", self.first, self.second)?;
+        match &self.crossing {
+            Crossing::Parallel => write!(f, "paths are parallel and never cross"),
+            Crossing::Coincident => write!(f, "paths are on the same line"),
+            Crossing::Past => write!(f, "paths crossed in the past"),
+            Crossing::Future { x, y } => {
+                let place = if self.inside { "inside" } else { "outside" };
+                write!(f, "paths cross {} the test area at x={}, y={}", place, x, y)
             }
         }
-        println!("");
-        println!("]");
+    }
+}
 
-        print!("[");
-        for e in b {
-            print!("{}, ", e);
+/// How the paths of every pair of hailstones cross, and whether they do so in the
+/// future inside the square `area`.
+fn collision_report(hailstones: &[Hailstone], area: (i128, i128)) -> Vec<PairReport> {
+    let mut report = Vec::new();
+    for (i, l) in hailstones.iter().enumerate() {
+        for (j, r) in hailstones.iter().enumerate().skip(i + 1) {
+            let crossing = l.crossing_x_y(r);
+            let inside = match &crossing {
+                Crossing::Future { x, y } => x.is_within(area) && y.is_within(area),
+                _ => false,
+            };
+            report.push(PairReport {
+                first: i + 1,
+                second: j + 1,
+                crossing,
+                inside,
+            });
         }
-        println!("]");
-    */
+    }
+    report
+}
+
+/// The number of paths that cross in the future inside the square `area` of the
+/// x-y plane.
+fn count_collisions_in_area(hailstones: &[Hailstone], area: (i128, i128)) -> usize {
+    collision_report(hailstones, area)
+        .iter()
+        .filter(|pair| pair.inside)
+        .count()
+}
+
+/// The time at which a hailstone starting at `start` with `speed` is on the line
+/// through the origin in `direction`, if it gets there at a whole time.
+fn time_on_line(start: Vectori128, speed: Vectori128, direction: Vectori128) -> Option<i128> {
+    // start + t * speed is parallel to the direction:
+    //     start x direction + t * (speed x direction) = 0
+    let a = start.cross(direction);
+    let b = speed.cross(direction);
+    let (a, b) = [(a.x, b.x), (a.y, b.y), (a.z, b.z)]
+        .into_iter()
+        .find(|&(_, b)| b != 0)?;
+    if a % b != 0 {
+        return None;
+    }
+    let t = -a / b;
+    (start + speed * t).cross(direction).is_zero().then_some(t)
+}
+
+/// The start and speed of a rock that hits the hailstones `a`, `b` and `c`, if
+/// they determine a single one.
+///
+/// All positions are taken relative to hailstone `a`, in which frame the rock goes
+/// through the origin. Its path then lies in the plane through the origin and the
+/// path of `b`, and in the one through the origin and the path of `c`, so it runs
+/// along the cross product of their normals. Where `b` and `c` cross that line,
+/// and when, gives the speed and the start.
+fn find_rock(a: &Hailstone, b: &Hailstone, c: &Hailstone) -> Option<(Vectori128, Vectori128)> {
+    let origin = a.start;
+    let drift = a.speed;
+    let relative = |h: &Hailstone| (h.start - origin, h.speed - drift);
+    let (start_b, speed_b) = relative(b);
+    let (start_c, speed_c) = relative(c);
+
+    // Reducing the normals keeps their cross product within an i128.
+    let normal_b = start_b.cross(speed_b).reduced()?;
+    let normal_c = start_c.cross(speed_c).reduced()?;
+    let direction = normal_b.cross(normal_c).reduced()?;
+
+    let t_b = time_on_line(start_b, speed_b, direction)?;
+    let t_c = time_on_line(start_c, speed_c, direction)?;
+    let hit_b = start_b + speed_b * t_b;
+    let hit_c = start_c + speed_c * t_c;
+    let speed = (hit_c - hit_b).div_exact(t_c - t_b)?;
+    let start = hit_b - speed * t_b;
+    Some((start + origin, speed + drift))
+}
+
+/// Whether a rock thrown from `start` with `speed` hits `hailstone` at some time.
+fn hits(start: Vectori128, speed: Vectori128, hailstone: &Hailstone) -> bool {
+    // The rock hits if the difference of the positions is a multiple of the
+    // difference of the speeds.
+    let offset = start - hailstone.start;
+    let closing = speed - hailstone.speed;
+    offset.cross(closing).is_zero() && (!closing.is_zero() || offset.is_zero())
+}
+
+/// The start and speed of the rock that hits every hailstone.
+fn throw_rock(hailstones: &[Hailstone]) -> Option<(Vectori128, Vectori128)> {
+    let (first, others) = hailstones.split_first()?;
+    others
+        .iter()
+        .enumerate()
+        .flat_map(|(i, b)| others[i + 1..].iter().map(move |c| (b, c)))
+        .filter_map(|(b, c)| find_rock(first, b, c))
+        .find(|&(start, speed)| hailstones.iter().all(|h| hits(start, speed, h)))
 }
 
-pub fn task1() -> crate::AOCResult<usize> {
-    let hailstones = parse();
-    let collisions = collisions_x_y(&hailstones);
-    // let count = count_collisions_in_area(collisions, (7f64, 27f64));
-    let count = count_collisions_in_area(collisions, (200000000000000f64, 400000000000000f64));
+pub struct Day24;
+
+impl Solution for Day24 {
+    const DAY: u32 = DAY;
+
+    type Input = Hailstorm;
+
+    fn parse(input: &InputSource) -> Result<Self::Input, Error> {
+        Ok(Hailstorm {
+            hailstones: parse::lines(DAY, 1, &input.lines()?, parse_line)?,
+            area: TEST_AREA,
+        })
+    }
 
-    crate::AOCResult {
-        day: 24,
-        task: 1,
-        r: count,
+    fn part1(hailstorm: &Self::Input) -> Result<Answer, Error> {
+        let count = count_collisions_in_area(&hailstorm.hailstones, hailstorm.area);
+
+        Ok(Answer::from(count))
+    }
+
+    fn part2(hailstorm: &Self::Input) -> Result<Answer, Error> {
+        let (start, _) = throw_rock(&hailstorm.hailstones)
+            .ok_or_else(|| Error::no_solution(DAY, "rock that hits every hailstone"))?;
+        Ok(Answer::from(start.x + start.y + start.z))
+    }
+
+    fn explain(hailstorm: &Self::Input, part: u32) -> Result<(Answer, Option<String>), Error> {
+        if part != 1 {
+            return Ok((Self::part2(hailstorm)?, None));
+        }
+        let report = collision_report(&hailstorm.hailstones, hailstorm.area);
+        let count = report.iter().filter(|pair| pair.inside).count();
+        let mut explanation = format!("{} paths cross inside the test area\n", count);
+        for pair in report {
+            explanation.push_str(&format!("{}\n", pair));
+        }
+        Ok((Answer::from(count), Some(explanation)))
     }
 }
 
-pub fn task2() -> crate::AOCResult<usize> {
-    let hailstones = parse();
-    help(&hailstones);
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::solution::{example, example_with, parse_example};
+
+    #[test]
+    fn example_part1() {
+        assert_eq!(
+            example_with::<Day24>(include_str!("examples/day24.txt"), 1, |hailstorm| {
+                hailstorm.area = (7, 27)
+            }),
+            Answer(2)
+        );
+    }
+
+    #[test]
+    fn example_part2() {
+        assert_eq!(
+            example::<Day24>(include_str!("examples/day24.txt"), 2),
+            Answer(47)
+        );
+    }
+
+    fn example_input() -> Hailstorm {
+        parse_example::<Day24>(include_str!("examples/day24.txt"))
+    }
+
+    #[test]
+    fn crossings() {
+        let input = example_input().hailstones;
+        let future = |x: (i128, i128), y: (i128, i128)| Crossing::Future {
+            x: Rational::new(x.0, x.1),
+            y: Rational::new(y.0, y.1),
+        };
+        assert_eq!(input[0].crossing_x_y(&input[1]), future((43, 3), (46, 3)));
+        assert_eq!(input[0].crossing_x_y(&input[2]), future((35, 3), (50, 3)));
+        assert_eq!(input[0].crossing_x_y(&input[3]), future((31, 5), (97, 5)));
+        assert_eq!(input[0].crossing_x_y(&input[4]), Crossing::Past);
+        assert_eq!(input[1].crossing_x_y(&input[2]), Crossing::Parallel);
+        assert_eq!(input[1].crossing_x_y(&input[3]), future((-6, 1), (-5, 1)));
+        assert_eq!(input[3].crossing_x_y(&input[4]), Crossing::Past);
+
+        let ahead = Hailstone {
+            start: Vectori128 { x: 24, y: 13, z: 0 },
+            speed: Vectori128 { x: -6, y: 3, z: 0 },
+        };
+        assert_eq!(input[1].crossing_x_y(&ahead), future((16, 1), (17, 1)));
+        let along = Hailstone {
+            start: Vectori128 { x: 16, y: 17, z: 0 },
+            speed: Vectori128 { x: 2, y: -1, z: 0 },
+        };
+        assert_eq!(ahead.crossing_x_y(&along), Crossing::Coincident);
+
+        assert_eq!(count_collisions_in_area(&input, (7, 27)), 2);
+        assert_eq!(count_collisions_in_area(&input, TEST_AREA), 0);
+    }
+
+    #[test]
+    fn report() {
+        let mut hailstorm = example_input();
+        hailstorm.area = (7, 27);
+        let (answer, report) = Day24::explain(&hailstorm, 1).unwrap();
+        assert_eq!(answer, Answer(2));
+        let report = report.unwrap();
+        let lines: Vec<&str> = report.lines().collect();
+        assert_eq!(lines.len(), 11);
+        assert_eq!(lines[0], "2 paths cross inside the test area");
+        assert_eq!(
+            lines[1..6],
+            [
+                "Hailstones 1 and 2: paths cross inside the test area at x=14.333, y=15.333",
+                "Hailstones 1 and 3: paths cross inside the test area at x=11.667, y=16.667",
+                "Hailstones 1 and 4: paths cross outside the test area at x=6.200, y=19.400",
+                "Hailstones 1 and 5: paths crossed in the past",
+                "Hailstones 2 and 3: paths are parallel and never cross",
+            ]
+        );
+        assert_eq!(lines[7], "Hailstones 2 and 5: paths crossed in the past");
+    }
 
-    crate::AOCResult {
-        day: 24,
-        task: 2,
-        // My coordinates that I got by throwing the linear equation system into a solver.
-        r: 129723668686742 + 353939130278484 + 227368817349775,
+    #[test]
+    fn rock_from_three_hailstones() {
+        let input = example_input().hailstones;
+        let rock = find_rock(&input[0], &input[1], &input[2]);
+        let expected = (
+            Vectori128 {
+                x: 24,
+                y: 13,
+                z: 10,
+            },
+            Vectori128 { x: -3, y: 1, z: 2 },
+        );
+        assert_eq!(rock, Some(expected));
+        assert!(input.iter().all(|h| hits(expected.0, expected.1, h)));
+        assert!(!hits(expected.0, expected.1 * 2, &input[0]));
     }
 }
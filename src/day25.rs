@@ -1,204 +1,298 @@
 use std::collections::{HashMap, HashSet, VecDeque};
-use std::fs::File;
-use std::io::{self, BufRead};
-use std::path::Path;
-
-fn lines_from_file(filename: impl AsRef<Path>) -> io::Result<impl Iterator<Item = String>> {
-    let file = File::open(&filename)?;
-    let reader = io::BufReader::new(file);
-    Ok(reader.lines().map(|l| l.expect("Could not parse line")))
-}
 
-struct Graph {
-    nodes: Vec<Node>
+use crate::dot::Dot;
+use crate::error::Error;
+use crate::input::InputSource;
+use crate::parse::{self, ParseError};
+use crate::solution::{Answer, Solution};
+
+const DAY: u32 = 25;
+
+/// The number of wires to disconnect.
+const CUT_SIZE: usize = 3;
+
+#[derive(Clone)]
+pub struct Graph {
+    nodes: Vec<Node>,
 }
 
+#[derive(Clone)]
 struct Node {
     name: u32,
+    label: String,
     connections: HashSet<u32>,
 }
 
-impl Graph {
+/// Wires whose removal splits the components into two groups.
+#[derive(Debug, PartialEq, Eq)]
+struct Cut {
+    /// The wires as pairs of node numbers, the smaller one first.
+    edges: Vec<(u32, u32)>,
+    /// The number of components on the side of the first node and on the other side.
+    sizes: (usize, usize),
+}
 
-    fn reachable(&self, start: u32) -> usize {
-        let mut seen = HashSet::new();
-        seen.insert(start);
-        let mut  queue = VecDeque::with_capacity(self.nodes.len());
-        queue.push_back(start);
-        while let Some(n) = queue.pop_front() {
-            for conn in &self.nodes[n as usize].connections {
-                if seen.insert(*conn) {
-                    queue.push_back(*conn);
-                }
-            }
-        }
-        seen.len()
-    }
+/// The flow through every wire in each direction, one at most.
+type Flow = HashMap<(u32, u32), i32>;
 
-    fn shortest_path(&self, start: u32, end: u32) -> Option<Vec<(u32, u32)>> {
+impl Graph {
+    /// The predecessor of every node on a shortest path from `source` through wires
+    /// that can take more flow, `None` for nodes that cannot be reached.
+    fn residual_paths(&self, flow: &Flow, source: u32) -> Vec<Option<u32>> {
         let mut predecessors = vec![None; self.nodes.len()];
-        predecessors[start as usize] = Some(start);
+        predecessors[source as usize] = Some(source);
 
         let mut queue = VecDeque::with_capacity(self.nodes.len());
-        queue.push_back(start);
+        queue.push_back(source);
         while let Some(n) = queue.pop_front() {
-            for succ in &self.nodes[n as usize].connections {
-                if predecessors[*succ as usize].is_some() {
+            for &succ in &self.nodes[n as usize].connections {
+                if predecessors[succ as usize].is_some()
+                    || flow.get(&(n, succ)).copied().unwrap_or(0) >= 1
+                {
                     continue;
                 }
-                predecessors[*succ as usize] = Some(n);
-                queue.push_back(*succ);
-                if *succ == end {
-                    break;
-                }
+                predecessors[succ as usize] = Some(n);
+                queue.push_back(succ);
             }
         }
-
-        predecessors[start as usize] = None;
-
-        if predecessors[end as usize].is_none() {
-            return None;
-        }
-
-        let mut last_node = end;
-        let mut r = Vec::new();
-
-        while let Some(pred) = predecessors[last_node as usize] {
-            r.push((last_node, pred));
-            last_node = pred;
-        }
-        Some(r)
+        predecessors
     }
 
-    fn remove_edge(&mut self, e: (u32, u32)) {
-        self.nodes[e.0 as usize].connections.remove(&e.1);
-        self.nodes[e.1 as usize].connections.remove(&e.0);
-    }
+    /// A cut of exactly `k` wires between the first node and another one. It is the
+    /// minimum cut between the two, found with the Edmonds-Karp algorithm, for the
+    /// first other node whose minimum cut has `k` wires. `None` if every other node
+    /// is separated from the first one by fewer or more wires.
+    fn cut_of_size(&self, k: usize) -> Option<Cut> {
+        let source = 0;
+        for sink in 1..self.nodes.len() as u32 {
+            let mut flow = Flow::new();
+            let mut paths = 0;
+            // One path more than `k` shows that no cut of `k` wires separates them.
+            while paths <= k {
+                let predecessors = self.residual_paths(&flow, source);
+                if predecessors[sink as usize].is_none() {
+                    break;
+                }
+                let mut n = sink;
+                while n != source {
+                    let pred = predecessors[n as usize].unwrap();
+                    *flow.entry((pred, n)).or_insert(0) += 1;
+                    *flow.entry((n, pred)).or_insert(0) -= 1;
+                    n = pred;
+                }
+                paths += 1;
+            }
+            if paths != k {
+                continue;
+            }
 
-    fn remove_edges(&mut self, edges: &Vec<(u32, u32)>) {
-        for e in edges {
-            self.remove_edge(*e);
+            // The nodes that can still be reached from the source form its side.
+            let side: Vec<bool> = self
+                .residual_paths(&flow, source)
+                .iter()
+                .map(Option::is_some)
+                .collect();
+            let mut edges: Vec<(u32, u32)> = self
+                .nodes
+                .iter()
+                .filter(|node| side[node.name as usize])
+                .flat_map(|node| {
+                    node.connections
+                        .iter()
+                        .filter(|&&succ| !side[succ as usize])
+                        .map(|&succ| (node.name.min(succ), node.name.max(succ)))
+                })
+                .collect();
+            edges.sort();
+            let size = side.iter().filter(|&&s| s).count();
+            return Some(Cut {
+                edges,
+                sizes: (size, self.nodes.len() - size),
+            });
         }
+        None
     }
+}
 
-    fn insert_edge(&mut self, e: (u32, u32)) {
-        self.nodes[e.0 as usize].connections.insert(e.1);
-        self.nodes[e.1 as usize].connections.insert(e.0);
-    }
+fn parse_line(line: &str) -> Result<(String, Vec<String>), ParseError> {
+    let (name, successors) = parse::split_once(line, ":")?;
+    let name = name.trim().to_owned();
+    let successors = successors
+        .split_whitespace()
+        .map(str::trim)
+        .map(str::to_owned)
+        .collect();
+    Ok((name, successors))
+}
 
-    fn insert_edges(&mut self, edges: &Vec<(u32, u32)>) {
-        for e in edges {
-            self.insert_edge(*e);
+/// The wiring, with the wires of `cut` drawn in red if there is one.
+fn wiring_graph(g: &Graph, cut: Option<&Cut>) -> Dot {
+    let mut dot = Dot::graph();
+    for node in &g.nodes {
+        dot.node(&node.label, &[]);
+    }
+    for node in &g.nodes {
+        let mut connections: Vec<u32> = node.connections.iter().copied().collect();
+        connections.sort();
+        for succ in connections.into_iter().filter(|&succ| node.name < succ) {
+            let other = &g.nodes[succ as usize].label;
+            if cut.is_some_and(|cut| cut.edges.contains(&(node.name, succ))) {
+                dot.edge(&node.label, other, &[("color", "red"), ("penwidth", "3")]);
+            } else {
+                dot.edge(&node.label, other, &[]);
+            }
         }
     }
-
+    dot
 }
 
-fn parse() -> Graph {
-    let node_list: Vec<(String, Vec<String>)> = lines_from_file("src/day25.txt")
-        .unwrap()
-        .map(|line| parse_line(&line))
-        .collect();
+fn split(g: &Graph) -> Result<Cut, Error> {
+    g.cut_of_size(CUT_SIZE).ok_or_else(|| {
+        let what = format!("{} wires that split the components in two", CUT_SIZE);
+        Error::no_solution(DAY, what)
+    })
+}
 
-    let mut hm : HashMap<String, u32> = HashMap::new();
-    let all_nodes = node_list.iter().flat_map(|(n, conns)| std::iter::once(n).chain(conns.iter()));
-    let mut num = 0;
-    let mut g = Graph { nodes: Vec::with_capacity(hm.len()) };
-    for node in all_nodes {
-        if let None = hm.get(node) {
-            hm.insert(node.to_owned(), num);
-            g.nodes.push(Node {
-                name: num,
-                connections: HashSet::new(),
-            });
-            num += 1;
+pub struct Day25;
+
+impl Solution for Day25 {
+    const DAY: u32 = DAY;
+    const PARTS: u32 = 1;
+
+    type Input = Graph;
+
+    fn parse(input: &InputSource) -> Result<Self::Input, Error> {
+        let node_list = parse::lines(DAY, 1, &input.lines()?, parse_line)?;
+
+        let mut hm: HashMap<String, u32> = HashMap::new();
+        let all_nodes = node_list
+            .iter()
+            .flat_map(|(n, conns)| std::iter::once(n).chain(conns.iter()));
+        let mut num = 0;
+        let mut g = Graph {
+            nodes: Vec::with_capacity(hm.len()),
+        };
+        for node in all_nodes {
+            if !hm.contains_key(node) {
+                hm.insert(node.to_owned(), num);
+                g.nodes.push(Node {
+                    name: num,
+                    label: node.to_owned(),
+                    connections: HashSet::new(),
+                });
+                num += 1;
+            }
         }
-    }
 
-    for (n, succs) in node_list {
-        let node_num = hm.get(&n).unwrap();
-        for succ in succs {
-            let other_node_num = hm.get(&succ).unwrap();
-            g.nodes[*node_num as usize].connections.insert(*other_node_num);
-            g.nodes[*other_node_num as usize].connections.insert(*node_num);
+        for (n, succs) in node_list {
+            let node_num = hm.get(&n).unwrap();
+            for succ in succs {
+                let other_node_num = hm.get(&succ).unwrap();
+                g.nodes[*node_num as usize]
+                    .connections
+                    .insert(*other_node_num);
+                g.nodes[*other_node_num as usize]
+                    .connections
+                    .insert(*node_num);
+            }
         }
+        Ok(g)
     }
-    g
 
-}
+    fn part1(input: &Self::Input) -> Result<Answer, Error> {
+        let cut = split(input)?;
 
-fn parse_line(line: &str) -> (String, Vec<String>) {
-    let mut iter = line.split(':');
-    let name = iter.next().unwrap().trim().to_owned();
-    let successors = iter.next().unwrap().split_whitespace().map(str::trim).map(str::to_owned).collect();
-    (name, successors)
-}
+        Ok(Answer::from(cut.sizes.0 * cut.sizes.1))
+    }
 
-fn _print_dot(graph: &Graph) {
-    use std::fmt::Write;
-    let mut s = String::new();
-    s.push_str("graph {\n");
-    for node in &graph.nodes {
-        for succ in &node.connections {
-            if node.name < *succ {
-                writeln!(s, "__{} -- __{}", node.name, succ).unwrap();
-            }
-        }
+    fn graph(input: &Self::Input) -> Result<Option<Dot>, Error> {
+        let cut = input.cut_of_size(CUT_SIZE);
+        Ok(Some(wiring_graph(input, cut.as_ref())))
     }
-    s.push_str("}");
-    println!("{}", s);
 }
 
-fn run_task_1(g: &mut Graph) -> Option<usize> {
-    let start = g.nodes[0].name;
-
-    // For each start and end node pair, try fo find
-    // 4 disjoint shortest paths
-    for potential_end in 1..g.nodes.len() {
-
-        // Find completely disjoint shortest paths by removing the edges of the already found shortest paths.
-        let sp1 = g.shortest_path(start, potential_end as u32).unwrap();
-        g.remove_edges(&sp1);
-        let sp2 = g.shortest_path(start, potential_end as u32).unwrap();
-        g.remove_edges(&sp2);
-        let sp3 = g.shortest_path(start, potential_end as u32).unwrap();
-        g.remove_edges(&sp3);
-
-        let sp4 = g.shortest_path(start, potential_end as u32);
-
-        if sp4.is_none() {
-            // Could only find three disjoint shortest paths.
-            // start and end nodes must be in different components now.
-            let num_reachable_from_start = g.reachable(start);
-            let num_reachable_from_end = g.reachable(potential_end as u32);
-            // _print_dot(&g);
-
-            // Repair graph
-            g.insert_edges(&sp3);
-            g.insert_edges(&sp2);
-            g.insert_edges(&sp1);
-
-            assert_eq!(g.nodes.len(), num_reachable_from_start + num_reachable_from_end);
-            return Some(num_reachable_from_start * num_reachable_from_end);
-        }
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::solution::{example, parse_example};
+
+    #[test]
+    fn example_part1() {
+        assert_eq!(
+            example::<Day25>(include_str!("examples/day25.txt"), 1),
+            Answer(54)
+        );
+    }
 
-        // Repair graph
-        g.insert_edges(&sp3);
-        g.insert_edges(&sp2);
-        g.insert_edges(&sp1);
+    /// The wires of `cut` by the names of the components they connect.
+    fn named(g: &Graph, cut: &Cut) -> Vec<(String, String)> {
+        let label = |n: u32| g.nodes[n as usize].label.clone();
+        cut.edges
+            .iter()
+            .map(|&(a, b)| (label(a), label(b)))
+            .collect()
     }
-    None
-}
 
-pub fn task1() -> crate::AOCResult<usize> {
-    let mut g = parse();
-    // print_dot(&g);
-    let r = run_task_1(&mut g).unwrap();
+    #[test]
+    fn cut_of_the_example() {
+        let g = parse_example::<Day25>(include_str!("examples/day25.txt"));
+        let cut = g.cut_of_size(3).unwrap();
+        assert_eq!(cut.sizes, (6, 9));
+        let mut wires: Vec<_> = named(&g, &cut)
+            .into_iter()
+            .map(|(a, b)| if a < b { (a, b) } else { (b, a) })
+            .collect();
+        wires.sort();
+        let expected = [("bvb", "cmg"), ("hfx", "pzl"), ("jqt", "nvd")];
+        assert_eq!(
+            wires,
+            expected.map(|(a, b)| (a.to_owned(), b.to_owned())).to_vec()
+        );
+
+        assert_eq!(g.cut_of_size(1), None);
+        assert_eq!(g.cut_of_size(2), None);
+    }
 
+    #[test]
+    fn cut_sizes() {
+        // A triangle with a tail of two nodes.
+        let g = parse_example::<Day25>("a: b c\nb: c\nc: d\nd: e");
+        let cut = g.cut_of_size(1).unwrap();
+        assert_eq!(named(&g, &cut), vec![("c".to_owned(), "d".to_owned())]);
+        assert_eq!(cut.sizes, (3, 2));
+        assert_eq!(g.cut_of_size(2).map(|cut| cut.sizes), Some((1, 4)));
+        assert_eq!(g.cut_of_size(3), None);
+
+        let error = split(&parse_example::<Day25>("a: b")).unwrap_err();
+        assert!(error.to_string().contains("3 wires"), "{}", error);
+    }
 
-    crate::AOCResult {
-        day: 25,
-        task: 1,
-        r,
+    #[test]
+    fn graph_with_the_cut_highlighted() {
+        let input = parse_example::<Day25>(include_str!("examples/day25.txt"));
+        let dot = Day25::graph(&input).unwrap().unwrap().to_string();
+        assert!(dot.starts_with("graph {\n  \"jqt\";\n"));
+        assert_eq!(dot.matches(" -- ").count(), 33);
+        let mut cut: Vec<&str> = dot
+            .lines()
+            .filter(|line| line.ends_with("[color=\"red\", penwidth=\"3\"];"))
+            .map(|line| line.trim().split(" [").next().unwrap())
+            .collect();
+        cut.sort();
+        assert_eq!(
+            cut,
+            vec![
+                "\"cmg\" -- \"bvb\"",
+                "\"jqt\" -- \"nvd\"",
+                "\"pzl\" -- \"hfx\"",
+            ]
+        );
+    }
+    #[test]
+    fn graph_without_a_cut() {
+        let input = parse_example::<Day25>("a: b c\nb: c\nc: d\nd: e");
+        let dot = Day25::graph(&input).unwrap().unwrap().to_string();
+        assert_eq!(dot.matches(" -- ").count(), 5);
+        assert!(!dot.contains("red"), "{}", dot);
     }
 }
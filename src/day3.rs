@@ -1,148 +1,130 @@
-use std::fs::File;
-use std::io::prelude::Read;
-use std::io::Result;
-
-fn read_file_to_string(filename: &str) -> Result<String> {
-    let mut file = File::open(filename)?;
-    let mut contents = String::new();
-    file.read_to_string(&mut contents)?;
-    Ok(contents)
-}
-
-#[derive(Debug)]
-struct Position {
-    pub line: usize,
-    pub column: usize,
-}
+use crate::error::Error;
+use crate::grid::Grid;
+use crate::input::InputSource;
+use crate::solution::{Answer, Solution};
 
 #[derive(Debug)]
 struct NumberMatch {
-    pub start: Position,
-    pub end: Position,
-    pub value: i64,
+    row: usize,
+    start_col: usize,
+    end_col: usize,
+    value: i64,
 }
 
 impl NumberMatch {
-    fn is_adjacent_to(&self, position: &Position) -> bool {
-        let num_line = self.start.line;
-        let symbol_line = position.line;
-
-        if symbol_line + 1 < num_line || num_line + 1 < symbol_line {
-            return false;
-        }
-
-        let num_start_col = self.start.column;
-        let num_end_col = self.end.column;
-        let symbol_col = position.column;
+    fn cells(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        (self.start_col..=self.end_col).map(|col| (self.row, col))
+    }
 
-        symbol_col + 1 >= num_start_col && symbol_col <= num_end_col + 1
+    fn is_adjacent_to(&self, schematic: &Grid<char>, position: (usize, usize)) -> bool {
+        position.0.abs_diff(self.row) <= 1
+            && self
+                .cells()
+                .any(|cell| schematic.neighbours8(cell).any(|n| n == position))
     }
 }
 
-fn parse_numbers(input: &str) -> Vec<NumberMatch> {
+fn is_symbol(c: char) -> bool {
+    !c.is_ascii_digit() && c != '.'
+}
+
+fn parse_numbers(schematic: &Grid<char>) -> Vec<NumberMatch> {
     let mut result = Vec::new();
-    let mut start = None;
-    let mut line = 0;
-    let mut column = 0;
-    for (i, c) in input.char_indices() {
-        match (c.is_ascii_digit(), start) {
-            (true, None) => start = Some((line, column, i)),
-            (false, Some((start_line, start_column, start_index))) => {
-                if let Ok(value) = input[start_index..i].parse() {
-                    result.push(NumberMatch {
-                        start: Position {
-                            line: start_line,
-                            column: start_column,
-                        },
-                        end: Position {
-                            line,
-                            column: column - 1,
-                        },
-                        value,
-                    });
+    for (row, line) in schematic.rows().enumerate() {
+        let mut start = None;
+        // A trailing '.' closes a number that ends the line.
+        for (col, c) in line.iter().chain(std::iter::once(&'.')).enumerate() {
+            match (c.is_ascii_digit(), start) {
+                (true, None) => start = Some(col),
+                (false, Some(start_col)) => {
+                    let digits: String = line[start_col..col].iter().collect();
+                    if let Ok(value) = digits.parse() {
+                        result.push(NumberMatch {
+                            row,
+                            start_col,
+                            end_col: col - 1,
+                            value,
+                        });
+                    }
+                    start = None;
                 }
-                start = None;
+                _ => {}
             }
-            _ => {}
-        }
-        if c == '\n' {
-            line += 1;
-            column = 0;
-            continue;
-        }
-        column += 1;
-    }
-    if let Some((start_line, start_column, start_index)) = start {
-        if let Ok(value) = input[start_index..].parse() {
-            result.push(NumberMatch {
-                start: Position {
-                    line: start_line,
-                    column: start_column,
-                },
-                end: Position { line, column },
-                value,
-            });
         }
     }
     result
 }
 
-fn find_special_chars(input: &str) -> Vec<(char, Position)> {
-    let mut result = Vec::new();
-    for (i, line) in input.lines().enumerate() {
-        for (j, c) in line.chars().enumerate() {
-            if !c.is_ascii_digit() && c != '.' && c != '\n' {
-                result.push((c, Position { line: i, column: j }));
-            }
-        }
+pub struct Day3;
+
+impl Solution for Day3 {
+    const DAY: u32 = 3;
+
+    type Input = Grid<char>;
+
+    fn parse(input: &InputSource) -> Result<Self::Input, Error> {
+        let rows = input.grid()?;
+        Ok(Grid::from_rows(
+            rows.iter().map(|row| row.chars().collect()).collect(),
+        ))
     }
-    result
-}
 
-pub fn task1() -> crate::AOCResult<i64> {
-    let schematic = read_file_to_string("src/day3.txt").unwrap();
-    let numbers = parse_numbers(&schematic);
-    let special_chars = find_special_chars(&schematic);
-
-    let part_num: i64 = numbers
-        .iter()
-        .filter(|num_match| {
-            special_chars
-                .iter()
-                .any(|(_, pos)| num_match.is_adjacent_to(pos))
-        })
-        .map(|num_match| num_match.value)
-        .sum();
-
-    crate::AOCResult {
-        day: 3,
-        task: 1,
-        r: part_num,
+    fn part1(schematic: &Self::Input) -> Result<Answer, Error> {
+        let numbers = parse_numbers(schematic);
+
+        let part_num: i64 = numbers
+            .iter()
+            .filter(|num_match| {
+                num_match
+                    .cells()
+                    .flat_map(|cell| schematic.neighbours8(cell))
+                    .any(|pos| is_symbol(schematic[pos]))
+            })
+            .map(|num_match| num_match.value)
+            .sum();
+
+        Ok(Answer::from(part_num))
+    }
+
+    fn part2(schematic: &Self::Input) -> Result<Answer, Error> {
+        let numbers = parse_numbers(schematic);
+
+        let sum: i64 = schematic
+            .iter()
+            .filter(|(_, &c)| c == '*')
+            .map(|(pos, _)| {
+                numbers
+                    .iter()
+                    .filter(|num_match| num_match.is_adjacent_to(schematic, pos))
+                    .collect::<Vec<&NumberMatch>>()
+            })
+            .filter(|numbers| numbers.len() == 2)
+            .map(|numbers| numbers.into_iter().map(|num_match| num_match.value))
+            .map(|numbers| numbers.product::<i64>())
+            .sum();
+
+        Ok(Answer::from(sum))
     }
 }
 
-pub fn task2() -> crate::AOCResult<i64> {
-    let schematic = read_file_to_string("src/day3.txt").unwrap();
-    let numbers = parse_numbers(&schematic);
-    let special_chars = find_special_chars(&schematic);
-
-    let sum: i64 = special_chars
-        .iter()
-        .filter(|(c, _)| *c == '*')
-        .map(|(_, pos)| {
-            numbers
-                .iter()
-                .filter(|num_match| num_match.is_adjacent_to(pos))
-                .collect::<Vec<&NumberMatch>>()
-        })
-        .filter(|numbers| numbers.len() == 2)
-        .map(|numbers| numbers.into_iter().map(|num_match| num_match.value))
-        .map(|numbers| numbers.product::<i64>())
-        .sum();
-
-    crate::AOCResult {
-        day: 3,
-        task: 2,
-        r: sum,
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::solution::example;
+
+    #[test]
+    fn example_part1() {
+        assert_eq!(
+            example::<Day3>(include_str!("examples/day3.txt"), 1),
+            Answer(4361)
+        );
+    }
+
+    #[test]
+    fn example_part2() {
+        assert_eq!(
+            example::<Day3>(include_str!("examples/day3.txt"), 2),
+            Answer(467835)
+        );
     }
 }
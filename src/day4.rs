@@ -1,12 +1,9 @@
-use std::fs::File;
-use std::io::{self, BufRead};
-use std::path::Path;
-
-fn lines_from_file(filename: impl AsRef<Path>) -> io::Result<impl Iterator<Item = String>> {
-    let file = File::open(&filename)?;
-    let reader = io::BufReader::new(file);
-    Ok(reader.lines().map(|l| l.expect("Could not parse line")))
-}
+use crate::error::Error;
+use crate::input::InputSource;
+use crate::parse::{self, ParseError};
+use crate::solution::{Answer, Solution};
+
+const DAY: u32 = 4;
 
 trait Task1 {
     fn calculate_value(&self) -> u32;
@@ -17,7 +14,7 @@ trait Task2 {
 }
 
 #[derive(Debug)]
-struct Card {
+pub struct Card {
     _id: u32,
     winning_numbers: Vec<u32>,
     own_numbers: Vec<u32>,
@@ -50,73 +47,79 @@ impl Task2 for Card {
     }
 }
 
-fn parse_card(line: &str) -> Card {
-    let mut iter = line.split(':');
-    let id = iter
-        .next()
-        .unwrap()
-        .split_ascii_whitespace()
-        .last()
-        .unwrap()
-        .parse::<u32>()
-        .unwrap();
-    let card_content = iter.next().unwrap();
-    let (winning_numbers, own_numbers) = parse_card_content(card_content);
-    Card {
+fn parse_card(line: &str) -> Result<Card, ParseError> {
+    let (card, card_content) = parse::split_once(line, ":")?;
+    let id = parse::number(parse::strip_prefix(card, "Card")?)?;
+    let (winning_numbers, own_numbers) = parse_card_content(card_content)?;
+    Ok(Card {
         _id: id,
         winning_numbers,
         own_numbers,
-    }
+    })
 }
 
-fn parse_card_content(card_content: &str) -> (Vec<u32>, Vec<u32>) {
-    let mut iter = card_content.split('|').map(str::trim);
+fn parse_card_content(card_content: &str) -> Result<(Vec<u32>, Vec<u32>), ParseError> {
+    let (winning_numbers, own_numbers) = parse::split_once(card_content, "|")?;
 
-    (
-        parse_numbers(iter.next().unwrap()),
-        parse_numbers(iter.next().unwrap()),
-    )
+    Ok((parse_numbers(winning_numbers)?, parse_numbers(own_numbers)?))
 }
 
-fn parse_numbers(numbers: &str) -> Vec<u32> {
+fn parse_numbers(numbers: &str) -> Result<Vec<u32>, ParseError> {
     numbers
         .split_ascii_whitespace()
-        .map(|n| n.parse::<u32>().unwrap())
+        .map(parse::number)
         .collect()
 }
 
-pub fn task1() -> crate::AOCResult<u32> {
-    let value: u32 = lines_from_file("src/day4.txt")
-        .unwrap()
-        .map(|line| parse_card(&line))
-        .map(|c| c.calculate_value())
-        .sum();
-
-    crate::AOCResult {
-        day: 4,
-        task: 1,
-        r: value,
+pub struct Day4;
+
+impl Solution for Day4 {
+    const DAY: u32 = DAY;
+
+    type Input = Vec<Card>;
+
+    fn parse(input: &InputSource) -> Result<Self::Input, Error> {
+        Ok(parse::lines(DAY, 1, &input.lines()?, parse_card)?)
     }
-}
 
-pub fn task2() -> crate::AOCResult<u64> {
-    let cards: Vec<Card> = lines_from_file("src/day4.txt")
-        .unwrap()
-        .map(|line| parse_card(&line))
-        .collect();
+    fn part1(input: &Self::Input) -> Result<Answer, Error> {
+        let value: u32 = input.iter().map(|c| c.calculate_value()).sum();
+
+        Ok(Answer::from(value))
+    }
 
-    let mut card_counts = vec![1_u64; cards.len()];
+    fn part2(cards: &Self::Input) -> Result<Answer, Error> {
+        let mut card_counts = vec![1_u64; cards.len()];
 
-    for (index, card) in cards.iter().enumerate() {
-        let value = card.calculate_num_matches() as usize;
-        for current_index in (index + 1)..=(usize::min(index + value, card_counts.len())) {
-            card_counts[current_index] += card_counts[index];
+        for (index, card) in cards.iter().enumerate() {
+            let value = card.calculate_num_matches() as usize;
+            for current_index in (index + 1)..=(usize::min(index + value, card_counts.len())) {
+                card_counts[current_index] += card_counts[index];
+            }
         }
+
+        Ok(Answer::from(card_counts.iter().sum::<u64>()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::solution::example;
+
+    #[test]
+    fn example_part1() {
+        assert_eq!(
+            example::<Day4>(include_str!("examples/day4.txt"), 1),
+            Answer(13)
+        );
     }
 
-    crate::AOCResult {
-        day: 4,
-        task: 2,
-        r: card_counts.iter().sum::<u64>(),
+    #[test]
+    fn example_part2() {
+        assert_eq!(
+            example::<Day4>(include_str!("examples/day4.txt"), 2),
+            Answer(30)
+        );
     }
 }
@@ -1,12 +1,19 @@
 use itertools::Itertools;
 use std::collections::BTreeMap;
-use std::fs::File;
-use std::io::prelude::Read;
-use std::io::Result;
 use std::ops::Range;
 
-struct ParsedInput {
+use crate::error::Error;
+use crate::input::{Block, InputSource};
+use crate::parse::{self, ParseError};
+use crate::solution::{Answer, Solution};
+
+const DAY: u32 = 5;
+
+pub struct ParsedInput {
     seeds: Vec<u64>,
+    /// The seeds read as pairs of a range start and a range length for part 2, or
+    /// why they cannot be.
+    seed_ranges: Result<Vec<Range<u64>>, ParseError>,
     seed_soil_map: RangeMap,
     soil_fertilizer_map: RangeMap,
     fertilizer_waper_map: RangeMap,
@@ -45,11 +52,6 @@ pub struct RangeMap {
     map: BTreeMap<u64, u64>,
 }
 
-#[cfg(windows)]
-const DOUBLE_LINE_ENDING: &str = "\r\n\r\n";
-#[cfg(not(windows))]
-const DOUBLE_LINE_ENDING: &str = "\n\n";
-
 impl RangeMap {
     fn new() -> Self {
         let mut map = BTreeMap::new();
@@ -64,12 +66,7 @@ impl RangeMap {
     }
 
     fn map(&self, value: u64) -> u64 {
-        let entry = self
-            .map
-            .iter()
-            .filter(|(&key, _)| key <= value)
-            .last()
-            .unwrap();
+        let entry = self.map.iter().rfind(|(&key, _)| key <= value).unwrap();
 
         (entry.1 + value) - entry.0
     }
@@ -109,97 +106,112 @@ impl RangeMap {
     }
 }
 
-fn read_file_to_string(filename: &str) -> Result<String> {
-    let mut file = File::open(filename)?;
-    let mut contents = String::new();
-    file.read_to_string(&mut contents)?;
-    Ok(contents)
-}
-
-fn parse() -> ParsedInput {
-    let text = read_file_to_string("src/day5.txt").unwrap();
-    let mut splits = text.split(DOUBLE_LINE_ENDING);
-    let seeds = parse_seeds(splits.next().unwrap().lines().next().unwrap());
-    let seed_soil_map = parse_map(splits.next().unwrap());
-    let soil_fertilizer_map = parse_map(splits.next().unwrap());
-    let fertilizer_waper_map = parse_map(splits.next().unwrap());
-    let water_light_map = parse_map(splits.next().unwrap());
-    let light_temperator_map = parse_map(splits.next().unwrap());
-    let temperature_humidity_map = parse_map(splits.next().unwrap());
-    let humidity_location_map = parse_map(splits.next().unwrap());
-    ParsedInput {
-        seeds,
-        seed_soil_map,
-        soil_fertilizer_map,
-        fertilizer_waper_map,
-        water_light_map,
-        light_temperator_map,
-        temperature_humidity_map,
-        humidity_location_map,
+fn parse_seeds(seed_line: &str) -> Result<Vec<u64>, ParseError> {
+    let seeds: Vec<u64> = parse::strip_prefix(seed_line, "seeds:")?
+        .split_ascii_whitespace()
+        .map(parse::number)
+        .collect::<Result<_, _>>()?;
+    if seeds.is_empty() {
+        return Err(ParseError::new(seed_line, "a list of seeds"));
     }
+    Ok(seeds)
 }
 
-fn parse_seeds(seed_line: &str) -> Vec<u64> {
-    seed_line[7..]
-        .split_ascii_whitespace()
-        .map(|s| s.parse().unwrap())
-        .collect()
+fn parse_seed_ranges(seed_line: &str, seeds: &[u64]) -> Result<Vec<Range<u64>>, ParseError> {
+    if !seeds.len().is_multiple_of(2) {
+        return Err(ParseError::new(
+            seed_line,
+            "pairs of seed range starts and lengths",
+        ));
+    }
+    Ok(seeds
+        .iter()
+        .tuples()
+        .map(|(&start, &size)| Range {
+            start,
+            end: start + size,
+        })
+        .collect())
 }
 
-fn parse_map(map_str: &str) -> RangeMap {
+fn parse_map(block: &Block) -> Result<RangeMap, ParseError> {
     let mut rm = RangeMap::new();
-    let iter = map_str.lines().skip(1);
-    for line in iter {
-        let mut line_iter = line.split_ascii_whitespace();
-        let mapped_start = line_iter.next().unwrap().parse::<u64>().unwrap();
-        let start = line_iter.next().unwrap().parse::<u64>().unwrap();
-        let size = line_iter.next().unwrap().parse::<u64>().unwrap();
+    let ranges = parse::lines(DAY, block.first_line + 1, &block.lines[1..], parse_range)?;
+    for (mapped_start, start, size) in ranges {
         rm.add_range(start, size, mapped_start);
     }
 
-    rm
+    Ok(rm)
 }
 
-pub fn task1() -> crate::AOCResult<u64> {
-    let parsed_input = parse();
-    let min_location = parsed_input
-        .seeds
-        .iter()
-        .map(|seed| parsed_input.translate_seed_to_location(*seed))
-        .min()
-        .unwrap();
-    crate::AOCResult {
-        day: 5,
-        task: 1,
-        r: min_location,
-    }
+fn parse_range(line: &str) -> Result<(u64, u64, u64), ParseError> {
+    let mut line_iter = line.split_ascii_whitespace();
+    let mapped_start = parse::number(parse::next(&mut line_iter, "a destination range start")?)?;
+    let start = parse::number(parse::next(&mut line_iter, "a source range start")?)?;
+    let size = parse::number(parse::next(&mut line_iter, "a range length")?)?;
+    Ok((mapped_start, start, size))
 }
 
-pub fn task2() -> crate::AOCResult<u64> {
-    let parsed_input = parse();
+pub struct Day5;
 
-    let min_location = parsed_input
-        .seeds
-        .iter()
-        .chunks(2)
-        .into_iter()
-        .map(|mut chunk| {
-            let start = *chunk.next().unwrap();
-            let size = chunk.next().unwrap();
-            Range {
-                start,
-                end: start + size,
-            }
+impl Solution for Day5 {
+    const DAY: u32 = DAY;
+
+    type Input = ParsedInput;
+
+    fn parse(input: &InputSource) -> Result<Self::Input, Error> {
+        let blocks = input.blocks()?;
+        let mut splits = blocks.iter();
+        let mut next_block = || {
+            splits
+                .next()
+                .ok_or_else(|| ParseError::missing("another section of the almanac").in_day(DAY))
+        };
+        let seeds_block = next_block()?;
+        let seed_line = &seeds_block.lines[0];
+        let seeds = parse_seeds(seed_line).map_err(|e| e.at(DAY, seeds_block.first_line))?;
+        let seed_ranges =
+            parse_seed_ranges(seed_line, &seeds).map_err(|e| e.at(DAY, seeds_block.first_line));
+        let seed_soil_map = parse_map(next_block()?)?;
+        let soil_fertilizer_map = parse_map(next_block()?)?;
+        let fertilizer_waper_map = parse_map(next_block()?)?;
+        let water_light_map = parse_map(next_block()?)?;
+        let light_temperator_map = parse_map(next_block()?)?;
+        let temperature_humidity_map = parse_map(next_block()?)?;
+        let humidity_location_map = parse_map(next_block()?)?;
+        Ok(ParsedInput {
+            seeds,
+            seed_ranges,
+            seed_soil_map,
+            soil_fertilizer_map,
+            fertilizer_waper_map,
+            water_light_map,
+            light_temperator_map,
+            temperature_humidity_map,
+            humidity_location_map,
         })
-        .flat_map(|range| parsed_input.map_range(&range))
-        .map(|range| range.start)
-        .min()
-        .unwrap();
-
-    crate::AOCResult {
-        day: 5,
-        task: 2,
-        r: min_location,
+    }
+
+    fn part1(parsed_input: &Self::Input) -> Result<Answer, Error> {
+        let min_location = parsed_input
+            .seeds
+            .iter()
+            .map(|seed| parsed_input.translate_seed_to_location(*seed))
+            .min()
+            .unwrap();
+        Ok(Answer::from(min_location))
+    }
+
+    fn part2(parsed_input: &Self::Input) -> Result<Answer, Error> {
+        let seed_ranges = parsed_input.seed_ranges.clone()?;
+        let min_location = seed_ranges
+            .iter()
+            .flat_map(|range| parsed_input.map_range(range))
+            .map(|range| range.start)
+            .min()
+            .unwrap();
+
+        Ok(Answer::from(min_location))
     }
 }
 
@@ -207,6 +219,29 @@ pub fn task2() -> crate::AOCResult<u64> {
 mod test {
 
     use super::*;
+    use crate::solution::example;
+
+    #[test]
+    fn invalid_seeds() {
+        let example = include_str!("examples/day5.txt");
+        let text = example.replacen("seeds: 79 14 55 13", "seeds:", 1);
+        let Err(error) = Day5::parse(&InputSource::Text(text)) else {
+            panic!();
+        };
+        assert_eq!(
+            error.to_string(),
+            "day 5, line 1: expected a list of seeds, found 'seeds:'"
+        );
+
+        // An odd number of seeds only fails part 2.
+        let text = example.replacen("seeds: 79 14 55 13", "seeds: 79 14 55", 1);
+        let input = Day5::parse(&InputSource::Text(text)).unwrap();
+        assert_eq!(Day5::part1(&input).unwrap(), Answer(43));
+        assert_eq!(
+            Day5::part2(&input).unwrap_err().to_string(),
+            "day 5, line 1: expected pairs of seed range starts and lengths, found 'seeds: 79 14 55'"
+        );
+    }
 
     #[test]
     fn test() {
@@ -229,7 +264,7 @@ mod test {
         rm.add_range(90, 10, 10);
 
         let mut mapped = rm.map_range(&Range { start: 0, end: 110 });
-        mapped.sort_by(|l, r| l.start.cmp(&r.start));
+        mapped.sort_by_key(|l| l.start);
 
         assert_eq!(
             mapped,
@@ -257,4 +292,20 @@ mod test {
             }]
         )
     }
+
+    #[test]
+    fn example_part1() {
+        assert_eq!(
+            example::<Day5>(include_str!("examples/day5.txt"), 1),
+            Answer(35)
+        );
+    }
+
+    #[test]
+    fn example_part2() {
+        assert_eq!(
+            example::<Day5>(include_str!("examples/day5.txt"), 2),
+            Answer(46)
+        );
+    }
 }
@@ -1,15 +1,12 @@
-use std::fs::File;
-use std::io::{self, BufRead};
-use std::path::Path;
-
-fn lines_from_file(filename: impl AsRef<Path>) -> io::Result<impl Iterator<Item = String>> {
-    let file = File::open(&filename)?;
-    let reader = io::BufReader::new(file);
-    Ok(reader.lines().map(|l| l.expect("Could not parse line")))
-}
+use crate::error::Error;
+use crate::input::InputSource;
+use crate::parse::{self, ParseError};
+use crate::solution::{Answer, Solution};
+
+const DAY: u32 = 6;
 
 #[derive(Debug)]
-struct Race {
+pub struct Race {
     time: u64,
     distance: u64,
 }
@@ -21,65 +18,102 @@ impl Race {
         let time_halved_squared = time_halved * time_halved;
         let distance = self.distance as f64;
         let root = (time_halved_squared - distance).sqrt();
+        // The record has to be beaten, so hold times hitting it exactly do not count.
         let lower_bound = time_halved - root;
-        let ceil_lower = lower_bound.ceil();
+        let first_win = lower_bound.floor() + 1f64;
         let upper_bound = time_halved + root;
-        let floor_upper = upper_bound.floor();
-        (floor_upper - ceil_lower) as u32 + 1
+        let last_win = upper_bound.ceil() - 1f64;
+        (last_win - first_win) as u32 + 1
     }
 }
 
-fn parse() -> Vec<Race> {
-    let mut lines = lines_from_file("src/day6.txt").unwrap();
-    let times = parse_numbers(&lines.next().unwrap());
-    let distance = parse_numbers(&lines.next().unwrap());
-
-    times
-        .into_iter()
-        .zip(distance)
-        .map(|(time, distance)| Race { time, distance })
+fn parse_numbers(line: &str, label: &str) -> Result<Vec<u64>, ParseError> {
+    parse::strip_prefix(line, label)?
+        .split_ascii_whitespace()
+        .map(parse::number)
         .collect()
 }
 
-fn parse_numbers(line: &str) -> Vec<u64> {
-    line.split_ascii_whitespace()
-        .skip(1)
-        .filter(|&num| !num.is_empty())
-        .map(|s| s.parse::<u64>().unwrap())
-        .collect()
-}
+pub struct Day6;
+
+impl Solution for Day6 {
+    const DAY: u32 = DAY;
+
+    type Input = Vec<Race>;
+
+    fn parse(input: &InputSource) -> Result<Self::Input, Error> {
+        let lines = input.lines()?;
+        if lines.len() < 2 {
+            return Err(
+                ParseError::missing("a line of times and a line of distances")
+                    .in_day(DAY)
+                    .into(),
+            );
+        }
+        let times = parse_numbers(&lines[0], "Time:").map_err(|e| e.at(DAY, 1))?;
+        let distance = parse_numbers(&lines[1], "Distance:").map_err(|e| e.at(DAY, 2))?;
+
+        Ok(times
+            .into_iter()
+            .zip(distance)
+            .map(|(time, distance)| Race { time, distance })
+            .collect())
+    }
 
-pub fn task1() -> crate::AOCResult<u32> {
-    let races = parse();
-    let possibilities: u32 = races.iter().map(Race::count_possible_wins).product();
+    fn part1(races: &Self::Input) -> Result<Answer, Error> {
+        let possibilities: u32 = races.iter().map(Race::count_possible_wins).product();
 
-    crate::AOCResult {
-        day: 6,
-        task: 1,
-        r: possibilities,
+        Ok(Answer::from(possibilities))
+    }
+
+    fn part2(races: &Self::Input) -> Result<Answer, Error> {
+        let a = races
+            .iter()
+            .fold((String::new(), String::new()), |acc, race| {
+                (
+                    format!("{}{}", acc.0, race.time),
+                    format!("{}{}", acc.1, race.distance),
+                )
+            });
+
+        let race = Race {
+            time: a.0.parse().unwrap(),
+            distance: a.1.parse().unwrap(),
+        };
+        let possibilities = race.count_possible_wins();
+
+        Ok(Answer::from(possibilities))
     }
 }
 
-pub fn task2() -> crate::AOCResult<u32> {
-    let races = parse();
-    let a = races
-        .iter()
-        .fold((String::new(), String::new()), |acc, race| {
-            (
-                format!("{}{}", acc.0, race.time),
-                format!("{}{}", acc.1, race.distance),
-            )
-        });
-
-    let race = Race {
-        time: a.0.parse().unwrap(),
-        distance: a.1.parse().unwrap(),
-    };
-    let possibilities = race.count_possible_wins();
-
-    crate::AOCResult {
-        day: 6,
-        task: 2,
-        r: possibilities,
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::solution::example;
+
+    #[test]
+    fn example_part1() {
+        assert_eq!(
+            example::<Day6>(include_str!("examples/day6.txt"), 1),
+            Answer(288)
+        );
+    }
+
+    #[test]
+    fn example_part2() {
+        assert_eq!(
+            example::<Day6>(include_str!("examples/day6.txt"), 2),
+            Answer(71503)
+        );
+    }
+
+    #[test]
+    fn record_ties_do_not_count() {
+        let wins = |time, distance| Race { time, distance }.count_possible_wins();
+        // Holding for 10 or 20 ms of 30 only ties the record of 200.
+        assert_eq!(wins(30, 200), 9);
+        // Only 5 ms beats 24, 4 and 6 ms tie it.
+        assert_eq!(wins(10, 24), 1);
+        assert_eq!(wins(7, 9), 4);
     }
 }
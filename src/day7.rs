@@ -1,15 +1,13 @@
 use std::collections::HashMap;
-use std::fs::File;
-use std::io::{self, BufRead};
-use std::path::Path;
 
 use itertools::Itertools;
 
-fn lines_from_file(filename: impl AsRef<Path>) -> io::Result<impl Iterator<Item = String>> {
-    let file = File::open(&filename)?;
-    let reader = io::BufReader::new(file);
-    Ok(reader.lines().map(|l| l.expect("Could not parse line")))
-}
+use crate::error::Error;
+use crate::input::InputSource;
+use crate::parse::{self, ParseError};
+use crate::solution::{Answer, Solution};
+
+const DAY: u32 = 7;
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 enum CardStrengh {
@@ -29,7 +27,7 @@ enum CardStrengh {
 }
 
 impl std::convert::TryFrom<char> for CardStrengh {
-    type Error = ();
+    type Error = ParseError;
 
     fn try_from(value: char) -> Result<Self, Self::Error> {
         use CardStrengh::*;
@@ -47,7 +45,7 @@ impl std::convert::TryFrom<char> for CardStrengh {
             '4' => Ok(Four),
             '3' => Ok(Three),
             '2' => Ok(Two),
-            _ => Err(()),
+            _ => Err(ParseError::new(&value.to_string(), "a card")),
         }
     }
 }
@@ -135,48 +133,77 @@ impl PartialOrd for Hand {
 }
 
 #[derive(Debug)]
-struct Bid {
+pub struct Bid {
     hand: Hand,
     bid: u32,
 }
 
-fn parse() -> Vec<Bid> {
-    lines_from_file("src/day7.txt")
-        .unwrap()
-        .map(|line| parse_line(&line))
-        .collect()
+// Both parts read the same hands, but score the jokers differently.
+
+fn parse_line(line: &str) -> Result<Bid, ParseError> {
+    let (cards, bid) = parse_cards_and_bid(line)?;
+    let [card1, card2, card3, card4, card5] = cards;
+
+    Ok(Bid {
+        hand: Hand(card1, card2, card3, card4, card5),
+        bid,
+    })
 }
 
-fn parse_line(line: &str) -> Bid {
+fn parse_cards_and_bid<C>(line: &str) -> Result<([C; 5], u32), ParseError>
+where
+    C: TryFrom<char, Error = ParseError>,
+{
     let mut iter = line.split_ascii_whitespace();
-    let mut cards = iter.next().unwrap().chars();
-    let card1 = CardStrengh::try_from(cards.next().unwrap()).unwrap();
-    let card2 = CardStrengh::try_from(cards.next().unwrap()).unwrap();
-    let card3 = CardStrengh::try_from(cards.next().unwrap()).unwrap();
-    let card4 = CardStrengh::try_from(cards.next().unwrap()).unwrap();
-    let card5 = CardStrengh::try_from(cards.next().unwrap()).unwrap();
+    let hand = parse::next(&mut iter, "a hand of cards")?;
+    let cards = hand
+        .chars()
+        .map(C::try_from)
+        .collect::<Result<Vec<C>, _>>()?
+        .try_into()
+        .map_err(|_| ParseError::new(hand, "a hand of 5 cards"))?;
+    let bid = parse::number(parse::next(&mut iter, "a bid")?)?;
+
+    Ok((cards, bid))
+}
 
-    let bid = iter.next().unwrap().parse::<u32>().unwrap();
+pub struct Day7;
 
-    Bid {
-        hand: Hand(card1, card2, card3, card4, card5),
-        bid,
+impl Solution for Day7 {
+    const DAY: u32 = DAY;
+
+    type Input = (Vec<Bid>, Vec<BidWithJoker>);
+
+    fn parse(input: &InputSource) -> Result<Self::Input, Error> {
+        let lines = input.lines()?;
+        Ok((
+            parse::lines(DAY, 1, &lines, parse_line)?,
+            parse::lines(DAY, 1, &lines, parse_line2)?,
+        ))
     }
-}
 
-pub fn task1() -> crate::AOCResult<u32> {
-    let mut bids = parse();
-    bids.sort_by(|l, r| l.hand.cmp(&r.hand));
-    let value: u32 = bids
-        .iter()
-        .enumerate()
-        .map(|(index, bid)| (index as u32 + 1) * bid.bid)
-        .sum();
-
-    crate::AOCResult {
-        day: 7,
-        task: 1,
-        r: value,
+    fn part1((bids, _): &Self::Input) -> Result<Answer, Error> {
+        let mut bids: Vec<&Bid> = bids.iter().collect();
+        bids.sort_by(|l, r| l.hand.cmp(&r.hand));
+        let value: u32 = bids
+            .iter()
+            .enumerate()
+            .map(|(index, bid)| (index as u32 + 1) * bid.bid)
+            .sum();
+
+        Ok(Answer::from(value))
+    }
+
+    fn part2((_, bids): &Self::Input) -> Result<Answer, Error> {
+        let mut bids: Vec<&BidWithJoker> = bids.iter().collect();
+        bids.sort_by(|l, r| l.hand.cmp(&r.hand));
+        let value: u32 = bids
+            .iter()
+            .enumerate()
+            .map(|(index, bid)| (index as u32 + 1) * bid.bid)
+            .sum();
+
+        Ok(Answer::from(value))
     }
 }
 
@@ -198,7 +225,7 @@ enum CardStrenghWithJoker {
 }
 
 impl std::convert::TryFrom<char> for CardStrenghWithJoker {
-    type Error = ();
+    type Error = ParseError;
 
     fn try_from(value: char) -> Result<Self, Self::Error> {
         use CardStrenghWithJoker::*;
@@ -216,7 +243,7 @@ impl std::convert::TryFrom<char> for CardStrenghWithJoker {
             '4' => Ok(Four),
             '3' => Ok(Three),
             '2' => Ok(Two),
-            _ => Err(()),
+            _ => Err(ParseError::new(&value.to_string(), "a card")),
         }
     }
 }
@@ -305,55 +332,26 @@ impl PartialOrd for HandWithJoker {
 }
 
 #[derive(Debug)]
-struct BidWithJoker {
+pub struct BidWithJoker {
     hand: HandWithJoker,
     bid: u32,
 }
 
-fn parse2() -> Vec<BidWithJoker> {
-    lines_from_file("src/day7.txt")
-        .unwrap()
-        .map(|line| parse_line2(&line))
-        .collect()
-}
+fn parse_line2(line: &str) -> Result<BidWithJoker, ParseError> {
+    let (cards, bid) = parse_cards_and_bid(line)?;
+    let [card1, card2, card3, card4, card5] = cards;
 
-fn parse_line2(line: &str) -> BidWithJoker {
-    let mut iter = line.split_ascii_whitespace();
-    let mut cards = iter.next().unwrap().chars();
-    let card1 = CardStrenghWithJoker::try_from(cards.next().unwrap()).unwrap();
-    let card2 = CardStrenghWithJoker::try_from(cards.next().unwrap()).unwrap();
-    let card3 = CardStrenghWithJoker::try_from(cards.next().unwrap()).unwrap();
-    let card4 = CardStrenghWithJoker::try_from(cards.next().unwrap()).unwrap();
-    let card5 = CardStrenghWithJoker::try_from(cards.next().unwrap()).unwrap();
-
-    let bid = iter.next().unwrap().parse::<u32>().unwrap();
-
-    BidWithJoker {
+    Ok(BidWithJoker {
         hand: HandWithJoker(card1, card2, card3, card4, card5),
         bid,
-    }
-}
-
-pub fn task2() -> crate::AOCResult<u32> {
-    let mut bids = parse2();
-    bids.sort_by(|l, r| l.hand.cmp(&r.hand));
-    let value: u32 = bids
-        .iter()
-        .enumerate()
-        .map(|(index, bid)| (index as u32 + 1) * bid.bid)
-        .sum();
-
-    crate::AOCResult {
-        day: 7,
-        task: 2,
-        r: value,
-    }
+    })
 }
 
 #[cfg(test)]
 mod test {
 
     use super::*;
+    use crate::solution::example;
 
     #[test]
     fn test_card_str_ord() {
@@ -361,6 +359,23 @@ mod test {
         assert!(Two < Three);
     }
 
+    #[test]
+    fn test_parse_line_errors() {
+        assert_eq!(parse_line("32T3K 765").unwrap().bid, 765);
+        assert_eq!(
+            parse_line("32X3K 765").err(),
+            Some(ParseError::new("X", "a card"))
+        );
+        assert_eq!(
+            parse_line("32T3 765").err(),
+            Some(ParseError::new("32T3", "a hand of 5 cards"))
+        );
+        assert_eq!(
+            parse_line("32T3K").err(),
+            Some(ParseError::missing("a bid"))
+        );
+    }
+
     #[test]
     fn test_hand_str_ord() {
         use CardStrengh::*;
@@ -396,4 +411,20 @@ mod test {
         assert!(FiveOfAKind > FourOfAKind);
         assert!(FourOfAKind > FullHouse);
     }
+
+    #[test]
+    fn example_part1() {
+        assert_eq!(
+            example::<Day7>(include_str!("examples/day7.txt"), 1),
+            Answer(6440)
+        );
+    }
+
+    #[test]
+    fn example_part2() {
+        assert_eq!(
+            example::<Day7>(include_str!("examples/day7.txt"), 2),
+            Answer(5905)
+        );
+    }
 }
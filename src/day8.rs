@@ -1,16 +1,19 @@
-use std::collections::HashMap;
-use std::fs::File;
-use std::io::{self, BufRead};
-use std::path::Path;
+use std::collections::{HashMap, HashSet};
 
-use itertools::Itertools;
+use crate::cycles::{self, Cycle};
+use crate::error::Error;
+use crate::input::InputSource;
+use crate::parse::{self, ParseError};
+use crate::solution::{Answer, Solution};
 
-enum Direction {
+const DAY: u32 = 8;
+
+pub enum Direction {
     Left,
     Right,
 }
 
-struct Graph {
+pub struct Graph {
     nodes: HashMap<String, Node>,
 }
 
@@ -19,106 +22,113 @@ struct Node {
     right_key: String,
 }
 
-fn lines_from_file(filename: impl AsRef<Path>) -> io::Result<impl Iterator<Item = String>> {
-    let file = File::open(&filename)?;
-    let reader = io::BufReader::new(file);
-    Ok(reader.lines().map(|l| l.expect("Could not parse line")))
-}
-
-fn parse() -> (Graph, Vec<Direction>) {
-    let mut graph = Graph {
-        nodes: HashMap::new(),
-    };
-    let mut iter = lines_from_file("src/day8.txt").unwrap();
-    let directions = parse_directions(&iter.next().unwrap());
-    iter.next();
-    iter.map(|line| parse_node(&line)).for_each(|(key, node)| {
-        graph.nodes.insert(key, node);
-    });
-
-    (graph, directions)
-}
-
-fn parse_directions(direction_str: &str) -> Vec<Direction> {
+fn parse_directions(direction_str: &str) -> Result<Vec<Direction>, ParseError> {
     direction_str
         .chars()
         .map(|c| match c {
-            'L' => Direction::Left,
-            'R' => Direction::Right,
-            _ => panic!("AAAAAAAhhhh"),
+            'L' => Ok(Direction::Left),
+            'R' => Ok(Direction::Right),
+            c => Err(ParseError::new(&c.to_string(), "'L' or 'R'")),
         })
         .collect()
 }
 
-fn parse_node(node_str: &str) -> (String, Node) {
-    let mut assignemt_split = node_str.split('=');
-    let key = assignemt_split.next().unwrap().trim().to_owned();
-    let mut lr_split = assignemt_split.next().unwrap().split(',');
-    let left_key = lr_split.next().unwrap().trim()[1..].to_owned();
-    let mut right = lr_split.next().unwrap().trim().chars();
-    right.next_back();
-    let right_key = right.as_str().to_owned();
-
-    (
-        key,
+fn parse_node(node_str: &str) -> Result<(String, Node), ParseError> {
+    let (key, lr) = parse::split_once(node_str, "=")?;
+    let lr = parse::strip_suffix(parse::strip_prefix(lr.trim(), "(")?, ")")?;
+    let (left_key, right_key) = parse::split_once(lr, ",")?;
+
+    Ok((
+        key.trim().to_owned(),
         Node {
-            left_key,
-            right_key,
+            left_key: left_key.trim().to_owned(),
+            right_key: right_key.trim().to_owned(),
         },
-    )
+    ))
 }
 
-pub fn task1() -> crate::AOCResult<i32> {
-    let (graph, directions) = parse();
+pub struct Day8;
 
-    let start: String = "AAA".to_owned();
-    let end: String = "ZZZ".to_owned();
-    let mut current_node = &start;
-    let mut steps = 0;
+impl Solution for Day8 {
+    const DAY: u32 = DAY;
 
-    'outer: loop {
-        for direction in &directions {
-            steps += 1;
-            match direction {
-                Direction::Left => {
-                    current_node = &graph.nodes.get(current_node).unwrap().left_key;
-                }
-                Direction::Right => {
-                    current_node = &graph.nodes.get(current_node).unwrap().right_key;
+    type Input = (Graph, Vec<Direction>);
+
+    fn parse(input: &InputSource) -> Result<Self::Input, Error> {
+        let blocks = input.blocks()?;
+        let (directions, nodes) = match blocks.as_slice() {
+            [directions, nodes, ..] => (directions, nodes),
+            _ => {
+                let error = ParseError::missing("a line of directions and a block of nodes");
+                return Err(error.in_day(DAY).into());
+            }
+        };
+        let directions = parse::lines(
+            DAY,
+            directions.first_line,
+            &directions.lines[..1],
+            parse_directions,
+        )?;
+        let node_list = parse::lines(DAY, nodes.first_line, &nodes.lines, parse_node)?;
+        let keys: HashSet<&String> = node_list.iter().map(|(key, _)| key).collect();
+        for (index, (_, node)) in node_list.iter().enumerate() {
+            for key in [&node.left_key, &node.right_key] {
+                if !keys.contains(key) {
+                    let error = ParseError::new(key, "the name of a node");
+                    return Err(error.at(DAY, nodes.first_line + index).into());
                 }
-            };
-            if current_node == &end {
-                break 'outer;
             }
         }
-    }
+        let graph = Graph {
+            nodes: node_list.into_iter().collect(),
+        };
 
-    crate::AOCResult {
-        day: 8,
-        task: 1,
-        r: steps,
+        Ok((graph, directions.into_iter().flatten().collect()))
     }
-}
 
-pub fn task2() -> crate::AOCResult<u64> {
-    let (graph, directions) = parse();
+    fn part1((graph, directions): &Self::Input) -> Result<Answer, Error> {
+        let start: String = "AAA".to_owned();
+        let end: String = "ZZZ".to_owned();
+        for key in [&start, &end] {
+            if !graph.nodes.contains_key(key) {
+                return Err(Error::no_solution(DAY, format!("node named {}", key)));
+            }
+        }
+        let mut current_node = &start;
+        let mut steps = 0;
+        // After visiting every node at every position in the directions, the walk
+        // can only go round in circles.
+        let max_steps = graph.nodes.len() * directions.len();
 
-    let current_nodes: Vec<&String> = graph
-        .nodes
-        .keys()
-        .filter(|key| key.ends_with('A'))
-        .collect();
-
-    // This only works, because the loops all start after 3 nodes, and the final node is the 3rd last of the loop...
-    let a = current_nodes
-        .iter()
-        .map(|start| foo(&graph, &directions, start))
-        .collect_vec();
-
-    crate::AOCResult {
-        day: 8,
-        task: 2,
-        r: a.iter().product::<u64>() * directions.len() as u64,
+        'outer: loop {
+            for direction in directions {
+                steps += 1;
+                match direction {
+                    Direction::Left => {
+                        current_node = &graph.nodes.get(current_node).unwrap().left_key;
+                    }
+                    Direction::Right => {
+                        current_node = &graph.nodes.get(current_node).unwrap().right_key;
+                    }
+                };
+                if current_node == &end {
+                    break 'outer;
+                }
+            }
+            if steps >= max_steps {
+                return Err(Error::no_solution(DAY, "way from AAA to ZZZ"));
+            }
+        }
+
+        Ok(Answer::from(steps))
+    }
+
+    fn part2((graph, directions): &Self::Input) -> Result<Answer, Error> {
+        let cycles = ghost_cycles(graph, directions);
+        let steps = cycles::first_common_hit(&cycles).ok_or_else(|| {
+            Error::no_solution(DAY, "step at which all ghosts are on a node ending in Z")
+        })?;
+        Ok(Answer::from(steps))
     }
 }
 
@@ -128,38 +138,147 @@ struct State<'a> {
     key: &'a String,
 }
 
-fn foo(graph: &Graph, directions: &Vec<Direction>, start: &String) -> u64 {
-    let mut states: HashMap<State, u64> = HashMap::new();
-    let mut current: &String = start;
+/// The steps at which the ghost starting on `start` is on a node that ends in Z.
+fn find_cycle(graph: &Graph, directions: &[Direction], start: &String) -> Cycle {
     let mut state = State {
         lr_index: 0,
-        key: current,
+        key: start,
     };
-    let mut path_index = 0;
-    let loop_size;
-
-    'outer: loop {
-        for (index, direction) in directions.iter().enumerate() {
-            states.insert(state.clone(), path_index);
-            match direction {
-                Direction::Left => {
-                    current = &graph.nodes.get(current).unwrap().left_key;
-                }
-                Direction::Right => {
-                    current = &graph.nodes.get(current).unwrap().right_key;
-                }
+    cycles::find_cycle(|| {
+        let current = state.clone();
+        let node = graph.nodes.get(state.key).unwrap();
+        state = State {
+            lr_index: (state.lr_index + 1) % directions.len(),
+            key: match directions[state.lr_index] {
+                Direction::Left => &node.left_key,
+                Direction::Right => &node.right_key,
+            },
+        };
+        let is_goal = current.key.ends_with('Z');
+        (current, is_goal)
+    })
+}
+
+/// The cycle of every ghost, starting on the nodes that end in A.
+fn ghost_cycles(graph: &Graph, directions: &[Direction]) -> Vec<Cycle> {
+    graph
+        .nodes
+        .keys()
+        .filter(|key| key.ends_with('A'))
+        .map(|start| find_cycle(graph, directions, start))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::solution::{example, parse_example};
+    use itertools::Itertools;
+
+    #[test]
+    fn example_part1() {
+        assert_eq!(
+            example::<Day8>(include_str!("examples/day8.txt"), 1),
+            Answer(2)
+        );
+    }
+
+    #[test]
+    fn example_part2() {
+        assert_eq!(
+            example::<Day8>(include_str!("examples/day8_part2.txt"), 2),
+            Answer(6)
+        );
+    }
+
+    /// Moves all ghosts at once until they are all on a goal.
+    fn brute_force((graph, directions): &(Graph, Vec<Direction>), max_steps: u64) -> Option<u64> {
+        let mut current: Vec<&String> = graph.nodes.keys().filter(|k| k.ends_with('A')).collect();
+        for (step, direction) in (0..max_steps).zip(directions.iter().cycle()) {
+            if current.iter().all(|key| key.ends_with('Z')) {
+                return Some(step);
             }
-            state = State {
-                lr_index: (index + 1) % directions.len(),
-                key: current,
-            };
-            path_index += 1;
-            if states.contains_key(&state) {
-                loop_size = path_index - states.get(&state).unwrap();
-                break 'outer;
+            for key in &mut current {
+                let node = &graph.nodes[*key];
+                *key = match direction {
+                    Direction::Left => &node.left_key,
+                    Direction::Right => &node.right_key,
+                };
             }
         }
+        None
+    }
+
+    #[test]
+    fn invalid_networks() {
+        let error = |text: &str| match Day8::parse(&InputSource::Text(text.to_owned())) {
+            Ok(input) => Day8::part1(&input).unwrap_err().to_string(),
+            Err(e) => e.to_string(),
+        };
+        assert_eq!(
+            error("LR\n\nAAA = (BBB, ZZZ)\nZZZ = (ZZZ, CCC)\n"),
+            "day 8, line 3: expected the name of a node, found 'BBB'"
+        );
+        assert_eq!(
+            error("L\n\n11A = (11Z, 11Z)\n11Z = (11A, 11A)\n"),
+            "day 8: found no node named AAA"
+        );
+        assert_eq!(
+            error("L\n\nAAA = (BBB, BBB)\nBBB = (AAA, AAA)\nZZZ = (AAA, AAA)\n"),
+            "day 8: found no way from AAA to ZZZ"
+        );
     }
 
-    loop_size / (directions.len() as u64)
+    #[test]
+    fn cycles() {
+        let input = parse_example::<Day8>(include_str!("examples/day8_part2.txt"));
+        let cycle = find_cycle(&input.0, &input.1, &"22A".to_owned());
+        assert_eq!(
+            cycle,
+            Cycle {
+                offset: 1,
+                length: 6,
+                prefix_hits: vec![],
+                hits: vec![3, 6],
+            }
+        );
+        assert!(cycle.is_hit_at(9));
+        assert!(!cycle.is_hit_at(10));
+    }
+
+    #[test]
+    fn ghosts_with_offsets() {
+        // 11A is on a goal at every even step from 2 on, 22A at steps 1, 4, 7, ...
+        // and 33A only once at step 1 before it gets stuck.
+        let nodes = [
+            "11A = (11B, 11B)",
+            "11B = (11Z, 11Z)",
+            "11Z = (11B, 11B)",
+            "22A = (22Z, 22Z)",
+            "22Z = (22B, 22B)",
+            "22B = (22C, 22C)",
+            "22C = (22Z, 22Z)",
+            "33A = (33Z, 33Z)",
+            "33Z = (33B, 33B)",
+            "33B = (33B, 33B)",
+        ];
+        let cases = [
+            ("1", Some(2)),
+            ("2", Some(1)),
+            ("12", Some(4)),
+            ("23", Some(1)),
+            ("13", None),
+            ("123", None),
+        ];
+        for (ghosts_in_case, expected) in cases {
+            let lines = nodes
+                .iter()
+                .filter(|line| ghosts_in_case.contains(&line[..1]))
+                .join("\n");
+            let input = parse_example::<Day8>(&format!("LR\n\n{}\n", lines));
+            let found = cycles::first_common_hit(&ghost_cycles(&input.0, &input.1));
+            assert_eq!(found, expected, "ghosts {}", ghosts_in_case);
+            assert_eq!(found, brute_force(&input, 100), "ghosts {}", ghosts_in_case);
+        }
+    }
 }
@@ -1,24 +1,19 @@
-use std::fs::File;
-use std::io::{self, BufRead};
-use std::path::Path;
-
-fn lines_from_file(filename: impl AsRef<Path>) -> io::Result<impl Iterator<Item = String>> {
-    let file = File::open(&filename)?;
-    let reader = io::BufReader::new(file);
-    Ok(reader.lines().map(|l| l.expect("Could not parse line")))
-}
-
-fn parse() -> Vec<Vec<i64>> {
-    lines_from_file("src/day9.txt")
-        .unwrap()
-        .map(|line| parse_line(&line))
-        .collect()
-}
-
-fn parse_line(line: &str) -> Vec<i64> {
-    line.split_ascii_whitespace()
-        .map(|word| word.parse::<i64>().unwrap())
-        .collect()
+use crate::error::Error;
+use crate::input::InputSource;
+use crate::parse::{self, ParseError};
+use crate::solution::{Answer, Solution};
+
+const DAY: u32 = 9;
+
+fn parse_line(line: &str) -> Result<Vec<i64>, ParseError> {
+    let history: Vec<i64> = line
+        .split_ascii_whitespace()
+        .map(parse::number)
+        .collect::<Result<_, _>>()?;
+    if history.is_empty() {
+        return Err(ParseError::missing("a history of values"));
+    }
+    Ok(history)
 }
 
 fn extrapolate_forwards(data: &Vec<i64>) -> i64 {
@@ -40,7 +35,7 @@ fn extrapolate_forwards(data: &Vec<i64>) -> i64 {
 
     let mut stack_iter = stack_data.iter();
     while let Some(data) = stack_iter.next_back() {
-        interpolated_value += data.last().unwrap();
+        interpolated_value += data.last().unwrap_or(&0);
     }
 
     interpolated_value + data.last().unwrap()
@@ -65,41 +60,40 @@ fn extrapolate_backwards(data: &Vec<i64>) -> i64 {
 
     let mut stack_iter = stack_data.iter();
     while let Some(data) = stack_iter.next_back() {
-        interpolated_value = data.first().unwrap() - interpolated_value;
+        interpolated_value = data.first().unwrap_or(&0) - interpolated_value;
     }
 
     data.first().unwrap() - interpolated_value
 }
 
-pub fn task1() -> crate::AOCResult<i64> {
-    let sum: i64 = parse()
-        .iter_mut()
-        .map(|data| extrapolate_forwards(data))
-        .sum();
+pub struct Day9;
+
+impl Solution for Day9 {
+    const DAY: u32 = DAY;
 
-    crate::AOCResult {
-        day: 9,
-        task: 1,
-        r: sum,
+    type Input = Vec<Vec<i64>>;
+
+    fn parse(input: &InputSource) -> Result<Self::Input, Error> {
+        Ok(parse::lines(DAY, 1, &input.lines()?, parse_line)?)
     }
-}
 
-pub fn task2() -> crate::AOCResult<i64> {
-    let sum: i64 = parse()
-        .iter_mut()
-        .map(|data| extrapolate_backwards(data))
-        .sum();
+    fn part1(input: &Self::Input) -> Result<Answer, Error> {
+        let sum: i64 = input.iter().map(extrapolate_forwards).sum();
+
+        Ok(Answer::from(sum))
+    }
+
+    fn part2(input: &Self::Input) -> Result<Answer, Error> {
+        let sum: i64 = input.iter().map(extrapolate_backwards).sum();
 
-    crate::AOCResult {
-        day: 9,
-        task: 2,
-        r: sum,
+        Ok(Answer::from(sum))
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::solution::example;
 
     #[test]
     fn extend_with_extrapolattion_test() {
@@ -112,4 +106,38 @@ mod test {
     fn extrapolate_backwards_test() {
         assert_eq!(5, extrapolate_backwards(&vec![10, 13, 16, 21, 30, 45]));
     }
+
+    #[test]
+    fn single_values() {
+        assert_eq!(5, extrapolate_forwards(&vec![5]));
+        assert_eq!(5, extrapolate_backwards(&vec![5]));
+    }
+
+    #[test]
+    fn empty_history() {
+        let text = "0 3 6\n\n1 3 6".to_owned();
+        let Err(error) = Day9::parse(&InputSource::Text(text)) else {
+            panic!();
+        };
+        assert_eq!(
+            error.to_string(),
+            "day 9, line 2: expected a history of values, found nothing"
+        );
+    }
+
+    #[test]
+    fn example_part1() {
+        assert_eq!(
+            example::<Day9>(include_str!("examples/day9.txt"), 1),
+            Answer(114)
+        );
+    }
+
+    #[test]
+    fn example_part2() {
+        assert_eq!(
+            example::<Day9>(include_str!("examples/day9.txt"), 2),
+            Answer(2)
+        );
+    }
 }
@@ -0,0 +1,99 @@
+use std::fmt;
+
+/// A graph in the DOT language of Graphviz, built up one statement at a time.
+/// Statements are written in the order they were added.
+pub struct Dot {
+    directed: bool,
+    statements: Vec<String>,
+}
+
+impl Dot {
+    pub fn graph() -> Dot {
+        Dot {
+            directed: false,
+            statements: Vec::new(),
+        }
+    }
+
+    pub fn digraph() -> Dot {
+        Dot {
+            directed: true,
+            statements: Vec::new(),
+        }
+    }
+
+    /// Sets `attributes` for every node added after this, such as their `shape`.
+    pub fn node_defaults(&mut self, attributes: &[(&str, &str)]) {
+        self.statements
+            .push(format!("node{}", attribute_list(attributes)));
+    }
+
+    pub fn node(&mut self, id: &str, attributes: &[(&str, &str)]) {
+        self.statements
+            .push(format!("{}{}", quote(id), attribute_list(attributes)));
+    }
+
+    pub fn edge(&mut self, from: &str, to: &str, attributes: &[(&str, &str)]) {
+        let op = if self.directed { "->" } else { "--" };
+        self.statements.push(format!(
+            "{} {} {}{}",
+            quote(from),
+            op,
+            quote(to),
+            attribute_list(attributes)
+        ));
+    }
+}
+
+impl fmt::Display for Dot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} {{", if self.directed { "digraph" } else { "graph" })?;
+        for statement in &self.statements {
+            writeln!(f, "  {};", statement)?;
+        }
+        writeln!(f, "}}")
+    }
+}
+
+fn quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn attribute_list(attributes: &[(&str, &str)]) -> String {
+    if attributes.is_empty() {
+        return String::new();
+    }
+    let attributes: Vec<String> = attributes
+        .iter()
+        .map(|(name, value)| format!("{}={}", name, quote(value)))
+        .collect();
+    format!(" [{}]", attributes.join(", "))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn undirected_graph() {
+        let mut dot = Dot::graph();
+        dot.node("a", &[]);
+        dot.edge("a", "b", &[("color", "red"), ("penwidth", "3")]);
+        assert_eq!(
+            dot.to_string(),
+            "graph {\n  \"a\";\n  \"a\" -- \"b\" [color=\"red\", penwidth=\"3\"];\n}\n"
+        );
+    }
+
+    #[test]
+    fn directed_graph_with_quoting() {
+        let mut dot = Dot::digraph();
+        dot.node_defaults(&[("shape", "box")]);
+        dot.node("say \"hi\"", &[("label", "a\\b")]);
+        dot.edge("x", "y", &[]);
+        assert_eq!(
+            dot.to_string(),
+            "digraph {\n  node [shape=\"box\"];\n  \"say \\\"hi\\\"\" [label=\"a\\\\b\"];\n  \"x\" -> \"y\";\n}\n"
+        );
+    }
+}
@@ -0,0 +1,62 @@
+use std::fmt;
+
+use crate::input::InputError;
+use crate::parse::ParseError;
+
+#[derive(Debug)]
+pub enum Error {
+    Input(InputError),
+    Parse(ParseError),
+    /// The input was read fine, but has no answer, such as no path to the goal.
+    NoSolution {
+        day: u32,
+        what: String,
+    },
+}
+
+impl Error {
+    pub fn no_solution(day: u32, what: impl Into<String>) -> Self {
+        Error::NoSolution {
+            day,
+            what: what.into(),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Input(e) => write!(f, "{}", e),
+            Error::Parse(e) => write!(f, "{}", e),
+            Error::NoSolution { day, what } => write!(f, "day {}: found no {}", day, what),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<InputError> for Error {
+    fn from(value: InputError) -> Self {
+        Error::Input(value)
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(value: ParseError) -> Self {
+        Error::Parse(value)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn no_solution() {
+        let error = Error::no_solution(17, "path to the bottom right block");
+        assert_eq!(
+            error.to_string(),
+            "day 17: found no path to the bottom right block"
+        );
+    }
+}
@@ -0,0 +1,137 @@
+/// One of the four directions on a map with north at the top.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Direction {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 4] = [
+        Direction::North,
+        Direction::East,
+        Direction::South,
+        Direction::West,
+    ];
+
+    pub fn turn_left(self) -> Direction {
+        match self {
+            Direction::North => Direction::West,
+            Direction::East => Direction::North,
+            Direction::South => Direction::East,
+            Direction::West => Direction::South,
+        }
+    }
+
+    pub fn turn_right(self) -> Direction {
+        match self {
+            Direction::North => Direction::East,
+            Direction::East => Direction::South,
+            Direction::South => Direction::West,
+            Direction::West => Direction::North,
+        }
+    }
+
+    pub fn reverse(self) -> Direction {
+        match self {
+            Direction::North => Direction::South,
+            Direction::East => Direction::West,
+            Direction::South => Direction::North,
+            Direction::West => Direction::East,
+        }
+    }
+
+    /// The change of `(row, col)` of one step in this direction.
+    pub fn delta(self) -> (isize, isize) {
+        match self {
+            Direction::North => (-1, 0),
+            Direction::East => (0, 1),
+            Direction::South => (1, 0),
+            Direction::West => (0, -1),
+        }
+    }
+}
+
+/// A position on a map, counted from the top left corner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Pos {
+    pub row: usize,
+    pub col: usize,
+}
+
+impl Pos {
+    pub fn new(row: usize, col: usize) -> Pos {
+        Pos { row, col }
+    }
+
+    /// The neighbouring position in `direction`, `None` if it would leave the
+    /// top or left edge.
+    pub fn step(self, direction: Direction) -> Option<Pos> {
+        let (d_row, d_col) = direction.delta();
+        Some(Pos {
+            row: self.row.checked_add_signed(d_row)?,
+            col: self.col.checked_add_signed(d_col)?,
+        })
+    }
+
+    /// The neighbouring position in `direction`, `None` if it would leave a map of
+    /// `width` times `height` tiles.
+    pub fn step_within(self, direction: Direction, width: usize, height: usize) -> Option<Pos> {
+        self.step(direction)
+            .filter(|pos| pos.row < height && pos.col < width)
+    }
+
+    pub fn manhattan_distance(self, other: Pos) -> usize {
+        self.row.abs_diff(other.row) + self.col.abs_diff(other.col)
+    }
+}
+
+impl From<(usize, usize)> for Pos {
+    fn from((row, col): (usize, usize)) -> Pos {
+        Pos { row, col }
+    }
+}
+
+impl From<Pos> for (usize, usize) {
+    fn from(pos: Pos) -> (usize, usize) {
+        (pos.row, pos.col)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn turns() {
+        for direction in Direction::ALL {
+            assert_eq!(direction.turn_left().turn_right(), direction);
+            assert_eq!(direction.turn_right().turn_right(), direction.reverse());
+            assert_eq!(direction.reverse().reverse(), direction);
+            let (d_row, d_col) = direction.delta();
+            assert_eq!(direction.reverse().delta(), (-d_row, -d_col));
+        }
+        assert_eq!(Direction::North.turn_left(), Direction::West);
+        assert_eq!(Direction::North.turn_right(), Direction::East);
+    }
+
+    #[test]
+    fn steps() {
+        let pos = Pos::new(0, 2);
+        assert_eq!(pos.step(Direction::North), None);
+        assert_eq!(pos.step(Direction::West), Some(Pos::new(0, 1)));
+        assert_eq!(pos.step_within(Direction::East, 3, 3), None);
+        assert_eq!(
+            pos.step_within(Direction::South, 3, 3),
+            Some(Pos::new(1, 2))
+        );
+        assert_eq!(Pos::new(2, 2).step_within(Direction::South, 3, 3), None);
+    }
+
+    #[test]
+    fn manhattan_distance() {
+        assert_eq!(Pos::new(6, 1).manhattan_distance(Pos::new(11, 5)), 9);
+        assert_eq!(Pos::new(11, 5).manhattan_distance(Pos::new(6, 1)), 9);
+    }
+}
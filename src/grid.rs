@@ -0,0 +1,329 @@
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+use crate::parse::ParseError;
+
+/// A rectangular 2D map stored row by row. Positions are `(row, col)` pairs with
+/// the origin in the top left corner.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    /// Builds a grid from its rows, which must all have the same length.
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Grid<T> {
+        let height = rows.len();
+        let width = rows.first().map_or(0, Vec::len);
+        assert!(
+            rows.iter().all(|row| row.len() == width),
+            "all rows of a grid must have the same length"
+        );
+        Grid {
+            width,
+            height,
+            cells: rows.into_iter().flatten().collect(),
+        }
+    }
+
+    /// Parses a character map with `f`, which returns `None` for characters that
+    /// are not `expected`. `first_line` is the 1-based line number of `rows[0]` in
+    /// the input.
+    pub fn parse(
+        day: u32,
+        first_line: usize,
+        rows: &[String],
+        f: impl Fn(char) -> Option<T>,
+        expected: &str,
+    ) -> Result<Grid<T>, ParseError> {
+        let width = rows.first().map_or(0, |row| row.chars().count());
+        let rows = crate::parse::lines(day, first_line, rows, |row| {
+            let cells: Vec<T> = row
+                .chars()
+                .map(|c| f(c).ok_or_else(|| ParseError::new(&c.to_string(), expected)))
+                .collect::<Result<_, _>>()?;
+            if cells.len() != width {
+                return Err(ParseError::new(row, format!("a row of {} cells", width)));
+            }
+            Ok(cells)
+        })?;
+        Ok(Grid::from_rows(rows))
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, (row, col): (usize, usize)) -> bool {
+        row < self.height && col < self.width
+    }
+
+    pub fn get(&self, pos: (usize, usize)) -> Option<&T> {
+        self.contains(pos)
+            .then(|| &self.cells[pos.0 * self.width + pos.1])
+    }
+
+    pub fn get_mut(&mut self, pos: (usize, usize)) -> Option<&mut T> {
+        if !self.contains(pos) {
+            return None;
+        }
+        Some(&mut self.cells[pos.0 * self.width + pos.1])
+    }
+
+    /// All positions, row by row.
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> + use<T> {
+        let width = self.width;
+        (0..self.height).flat_map(move |row| (0..width).map(move |col| (row, col)))
+    }
+
+    /// All cells with their positions, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        self.positions().zip(&self.cells)
+    }
+
+    /// The position of the first cell, row by row, matching `predicate`.
+    pub fn find(&self, predicate: impl Fn(&T) -> bool) -> Option<(usize, usize)> {
+        self.iter()
+            .find(|(_, cell)| predicate(cell))
+            .map(|(pos, _)| pos)
+    }
+
+    fn offsets(
+        &self,
+        (row, col): (usize, usize),
+        offsets: &'static [(isize, isize)],
+    ) -> impl Iterator<Item = (usize, usize)> + use<'_, T> {
+        offsets.iter().filter_map(move |&(d_row, d_col)| {
+            let pos = (
+                row.checked_add_signed(d_row)?,
+                col.checked_add_signed(d_col)?,
+            );
+            self.contains(pos).then_some(pos)
+        })
+    }
+
+    /// The positions above, right of, below and left of `pos` that are inside the
+    /// grid.
+    pub fn neighbours4(&self, pos: (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.offsets(pos, &[(-1, 0), (0, 1), (1, 0), (0, -1)])
+    }
+
+    /// The up to eight positions around `pos`, including the diagonal ones, that
+    /// are inside the grid.
+    pub fn neighbours8(&self, pos: (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.offsets(
+            pos,
+            &[
+                (-1, -1),
+                (-1, 0),
+                (-1, 1),
+                (0, 1),
+                (1, 1),
+                (1, 0),
+                (1, -1),
+                (0, -1),
+            ],
+        )
+    }
+
+    pub fn row(&self, row: usize) -> &[T] {
+        &self.cells[row * self.width..(row + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        (0..self.height).map(|row| self.row(row))
+    }
+
+    pub fn column(&self, col: usize) -> impl Iterator<Item = &T> {
+        assert!(col < self.width, "column {} is out of bounds", col);
+        self.cells.iter().skip(col).step_by(self.width)
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(|col| self.column(col))
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+
+    pub fn swap(&mut self, a: (usize, usize), b: (usize, usize)) {
+        assert!(self.contains(a) && self.contains(b));
+        self.cells
+            .swap(a.0 * self.width + a.1, b.0 * self.width + b.1);
+    }
+
+    /// Renders the grid with one character per cell and one line per row.
+    pub fn render(&self, f: impl Fn(&T) -> char) -> String {
+        let mut s = String::with_capacity((self.width + 1) * self.height);
+        for row in self.rows() {
+            s.extend(row.iter().map(&f));
+            s.push('\n');
+        }
+        s
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn filled(width: usize, height: usize, value: T) -> Grid<T> {
+        Grid {
+            width,
+            height,
+            cells: vec![value; width * height],
+        }
+    }
+
+    /// Mirrors the grid along its main diagonal, turning rows into columns.
+    pub fn transpose(&self) -> Grid<T> {
+        Grid {
+            width: self.height,
+            height: self.width,
+            cells: self.columns().flatten().cloned().collect(),
+        }
+    }
+
+    /// Rotates the grid by 90 degrees clockwise: the first column, read bottom to
+    /// top, becomes the first row.
+    pub fn rotate_clockwise(&self) -> Grid<T> {
+        Grid {
+            width: self.height,
+            height: self.width,
+            cells: self
+                .columns()
+                .flat_map(|col| col.cloned().collect::<Vec<_>>().into_iter().rev())
+                .collect(),
+        }
+    }
+
+    /// Rotates the grid by 90 degrees counterclockwise.
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn rotate_counterclockwise(&self) -> Grid<T> {
+        Grid {
+            width: self.height,
+            height: self.width,
+            cells: (0..self.width)
+                .rev()
+                .flat_map(|col| self.column(col).cloned())
+                .collect(),
+        }
+    }
+}
+
+impl Grid<char> {
+    /// Reads a character map whose cells all have to be one of `allowed`.
+    pub fn parse_chars(day: u32, rows: &[String], allowed: &str) -> Result<Grid<char>, ParseError> {
+        let expected = format!("one of '{}'", allowed);
+        Grid::parse(
+            day,
+            1,
+            rows,
+            |c| allowed.contains(c).then_some(c),
+            &expected,
+        )
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: (usize, usize)) -> &T {
+        self.get(pos)
+            .unwrap_or_else(|| panic!("{:?} is outside of the grid", pos))
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, pos: (usize, usize)) -> &mut T {
+        self.get_mut(pos)
+            .unwrap_or_else(|| panic!("{:?} is outside of the grid", pos))
+    }
+}
+
+impl fmt::Display for Grid<char> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.render(|&c| c))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn grid(rows: &[&str]) -> Grid<char> {
+        Grid::from_rows(rows.iter().map(|row| row.chars().collect()).collect())
+    }
+
+    #[test]
+    fn parse() {
+        let rows = vec!["#.".to_owned(), ".S".to_owned()];
+        let parsed = Grid::parse_chars(21, &rows, ".#S").unwrap();
+        assert_eq!((parsed.width(), parsed.height()), (2, 2));
+        assert_eq!(parsed[(1, 1)], 'S');
+        assert_eq!(parsed.find(|&c| c == 'S'), Some((1, 1)));
+
+        let rows = vec!["#.".to_owned(), ".x".to_owned()];
+        assert_eq!(
+            Grid::parse_chars(21, &rows, ".#S").unwrap_err().to_string(),
+            "day 21, line 2: expected one of '.#S', found 'x'"
+        );
+
+        let digits = Grid::parse(17, 1, &["19".to_owned()], |c| c.to_digit(10), "a digit");
+        assert_eq!(digits.unwrap().row(0), &[1, 9]);
+
+        let ragged = ["19".to_owned(), "1".to_owned()];
+        let error = Grid::parse(13, 5, &ragged, |c| c.to_digit(10), "a digit").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "day 13, line 6: expected a row of 2 cells, found '1'"
+        );
+    }
+
+    #[test]
+    fn neighbours() {
+        let g = Grid::filled(3, 2, 0);
+        let n4: Vec<_> = g.neighbours4((0, 0)).collect();
+        assert_eq!(n4, vec![(0, 1), (1, 0)]);
+        let n4: Vec<_> = g.neighbours4((1, 1)).collect();
+        assert_eq!(n4, vec![(0, 1), (1, 2), (1, 0)]);
+        let n8: Vec<_> = g.neighbours8((0, 1)).collect();
+        assert_eq!(n8, vec![(0, 2), (1, 2), (1, 1), (1, 0), (0, 0)]);
+        assert_eq!(g.neighbours8((5, 5)).count(), 0);
+        assert_eq!(g.get((2, 0)), None);
+    }
+
+    #[test]
+    fn rows_and_columns() {
+        let g = grid(&["abc", "def"]);
+        let rows: Vec<String> = g.rows().map(|row| row.iter().collect()).collect();
+        assert_eq!(rows, vec!["abc", "def"]);
+        let columns: Vec<String> = g.columns().map(|col| col.collect()).collect();
+        assert_eq!(columns, vec!["ad", "be", "cf"]);
+    }
+
+    #[test]
+    fn transform() {
+        let g = grid(&["abc", "def"]);
+        assert_eq!(g.transpose(), grid(&["ad", "be", "cf"]));
+        assert_eq!(g.rotate_clockwise(), grid(&["da", "eb", "fc"]));
+        assert_eq!(g.rotate_counterclockwise(), grid(&["cf", "be", "ad"]));
+        assert_eq!(
+            g.rotate_clockwise()
+                .rotate_clockwise()
+                .rotate_clockwise()
+                .rotate_clockwise(),
+            g
+        );
+        assert_eq!(g.to_string(), "abc\ndef\n");
+        assert_eq!(g.render(|&c| c.to_ascii_uppercase()), "ABC\nDEF\n");
+    }
+}
@@ -0,0 +1,262 @@
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, Read};
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+pub const DEFAULT_INPUTS_DIR: &str = "src";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputSource {
+    File(PathBuf),
+    Stdin,
+    /// Input held in memory, like the puzzle examples of the tests.
+    #[cfg_attr(not(test), allow(dead_code))]
+    Text(String),
+}
+
+impl InputSource {
+    fn open(&self) -> io::Result<Box<dyn BufRead>> {
+        match self {
+            InputSource::File(path) => Ok(Box::new(io::BufReader::new(File::open(path)?))),
+            InputSource::Stdin => Ok(Box::new(stdin_contents()?.as_bytes())),
+            InputSource::Text(text) => Ok(Box::new(io::Cursor::new(text.clone()))),
+        }
+    }
+
+    fn error(&self, line: Option<usize>, kind: InputErrorKind) -> InputError {
+        InputError {
+            source: self.clone(),
+            line,
+            kind,
+        }
+    }
+
+    /// The whole input as a single string.
+    pub fn text(&self) -> Result<String, InputError> {
+        let mut contents = String::new();
+        self.open()
+            .and_then(|mut reader| reader.read_to_string(&mut contents))
+            .map_err(|e| self.error(None, InputErrorKind::Io(e)))?;
+        Ok(contents)
+    }
+
+    /// The input split into lines, without line endings.
+    pub fn lines(&self) -> Result<Vec<String>, InputError> {
+        let reader = self
+            .open()
+            .map_err(|e| self.error(None, InputErrorKind::Io(e)))?;
+        reader
+            .lines()
+            .enumerate()
+            .map(|(index, line)| {
+                line.map_err(|e| self.error(Some(index + 1), InputErrorKind::Io(e)))
+            })
+            .collect()
+    }
+
+    /// The input split into blocks of lines separated by blank lines.
+    pub fn blocks(&self) -> Result<Vec<Block>, InputError> {
+        let mut blocks = Vec::new();
+        let mut current = Block {
+            first_line: 1,
+            lines: Vec::new(),
+        };
+        for (index, line) in self.lines()?.into_iter().enumerate() {
+            if line.trim().is_empty() {
+                if !current.lines.is_empty() {
+                    blocks.push(std::mem::take(&mut current));
+                }
+            } else {
+                if current.lines.is_empty() {
+                    current.first_line = index + 1;
+                }
+                current.lines.push(line);
+            }
+        }
+        if !current.lines.is_empty() {
+            blocks.push(current);
+        }
+        Ok(blocks)
+    }
+
+    /// The rows of a rectangular character map.
+    pub fn grid(&self) -> Result<Vec<String>, InputError> {
+        let mut rows = self.lines()?;
+        while rows.last().is_some_and(|row| row.is_empty()) {
+            rows.pop();
+        }
+        let width = match rows.first() {
+            Some(row) if !row.is_empty() => row.len(),
+            _ => return Err(self.error(None, InputErrorKind::Empty)),
+        };
+        if let Some((index, row)) = rows.iter().enumerate().find(|(_, row)| row.len() != width) {
+            return Err(self.error(
+                Some(index + 1),
+                InputErrorKind::RaggedGrid {
+                    expected: width,
+                    found: row.len(),
+                },
+            ));
+        }
+        Ok(rows)
+    }
+}
+
+impl fmt::Display for InputSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputSource::File(path) => write!(f, "{}", path.display()),
+            InputSource::Stdin => write!(f, "<stdin>"),
+            InputSource::Text(_) => write!(f, "<text>"),
+        }
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Block {
+    /// 1-based line number of the first line of the block.
+    pub first_line: usize,
+    pub lines: Vec<String>,
+}
+
+#[derive(Debug)]
+pub enum InputErrorKind {
+    Io(io::Error),
+    Empty,
+    RaggedGrid { expected: usize, found: usize },
+}
+
+#[derive(Debug)]
+pub struct InputError {
+    pub source: InputSource,
+    pub line: Option<usize>,
+    pub kind: InputErrorKind,
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{}: ", self.source, line)?,
+            None => write!(f, "{}: ", self.source)?,
+        }
+        match &self.kind {
+            InputErrorKind::Io(e) => write!(f, "{}", e),
+            InputErrorKind::Empty => write!(f, "input is empty"),
+            InputErrorKind::RaggedGrid { expected, found } => write!(
+                f,
+                "expected a row of {} characters, found {}",
+                expected, found
+            ),
+        }
+    }
+}
+
+impl std::error::Error for InputError {}
+
+// Both tasks of a day read the same input, but stdin can only be consumed once.
+static STDIN: OnceLock<String> = OnceLock::new();
+
+fn stdin_contents() -> io::Result<&'static str> {
+    if let Some(contents) = STDIN.get() {
+        return Ok(contents);
+    }
+    let mut contents = String::new();
+    io::stdin().read_to_string(&mut contents)?;
+    Ok(STDIN.get_or_init(|| contents))
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct InputOptions {
+    pub source: Option<InputSource>,
+    pub dir: PathBuf,
+}
+
+impl Default for InputOptions {
+    fn default() -> Self {
+        InputOptions {
+            source: None,
+            dir: PathBuf::from(DEFAULT_INPUTS_DIR),
+        }
+    }
+}
+
+impl InputOptions {
+    pub fn source_for(&self, day: u32) -> InputSource {
+        self.source
+            .clone()
+            .unwrap_or_else(|| InputSource::File(self.dir.join(format!("day{}.txt", day))))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn source(name: &str, contents: &str) -> InputSource {
+        let path = std::env::temp_dir().join(format!("aoc-2023-{}-{}", std::process::id(), name));
+        std::fs::write(&path, contents).unwrap();
+        InputSource::File(path)
+    }
+
+    #[test]
+    fn read_lines_and_blocks() {
+        let input = source("blocks", "a\r\nb\r\n\r\nc\n\n\nd\n");
+        assert_eq!(input.lines().unwrap(), vec!["a", "b", "", "c", "", "", "d"]);
+        let blocks = input.blocks().unwrap();
+        assert_eq!(
+            blocks.iter().map(|b| b.first_line).collect::<Vec<_>>(),
+            vec![1, 4, 7]
+        );
+        assert_eq!(
+            blocks.iter().map(|b| b.lines.clone()).collect::<Vec<_>>(),
+            vec![vec!["a", "b"], vec!["c"], vec!["d"]]
+        );
+    }
+
+    #[test]
+    fn read_grid() {
+        let input = source("grid", "#.#\n...\n\n");
+        assert_eq!(input.grid().unwrap(), vec!["#.#", "..."]);
+
+        let input = source("ragged", "#.#\n..\n");
+        let error = input.grid().unwrap_err();
+        assert_eq!(error.line, Some(2));
+        assert!(matches!(
+            error.kind,
+            InputErrorKind::RaggedGrid {
+                expected: 3,
+                found: 2
+            }
+        ));
+
+        let input = source("empty", "");
+        assert!(matches!(
+            input.grid().unwrap_err().kind,
+            InputErrorKind::Empty
+        ));
+    }
+
+    #[test]
+    fn missing_file() {
+        let input = InputSource::File(PathBuf::from("does/not/exist.txt"));
+        let error = input.text().unwrap_err();
+        assert!(matches!(error.kind, InputErrorKind::Io(_)));
+        assert!(error.to_string().starts_with("does/not/exist.txt: "));
+    }
+
+    #[test]
+    fn source_for_day() {
+        let options = InputOptions::default();
+        assert_eq!(
+            options.source_for(7),
+            InputSource::File(PathBuf::from("src/day7.txt"))
+        );
+
+        let options = InputOptions {
+            source: Some(InputSource::Stdin),
+            dir: PathBuf::from("inputs"),
+        };
+        assert_eq!(options.source_for(7), InputSource::Stdin);
+    }
+}
@@ -1,3 +1,7 @@
+mod answers;
+mod bench;
+mod cli;
+mod cycles;
 mod day1;
 mod day10;
 mod day11;
@@ -23,73 +27,293 @@ mod day6;
 mod day7;
 mod day8;
 mod day9;
+mod dot;
+mod error;
+mod geometry;
+mod grid;
+mod input;
+mod parse;
+mod pool;
+mod registry;
+mod report;
+mod search;
+mod solution;
+mod trace;
 
-pub struct AOCResult<A> {
-    day: u32,
-    task: u32,
-    r: A,
+fn select_tasks(selection: &cli::Selection) -> Vec<registry::Task> {
+    match registry::select(selection) {
+        Ok(tasks) => tasks,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            std::process::exit(2);
+        }
+    }
 }
 
-fn measure_time<R: std::fmt::Display, F: FnOnce() -> AOCResult<R>>(f: F) {
-    let now = std::time::Instant::now();
-    let r = f();
-    println!(
-        "Day {:2}, Task {}: {:18} in {:12} us",
-        r.day,
-        r.task,
-        r.r,
-        now.elapsed().as_micros()
+/// Runs the selected days on `run.jobs()` threads, calling `on_result` for each
+/// day in day order. Input errors are reported in the same order. Returns whether
+/// the input of any day failed.
+fn run_days(run: &cli::RunArgs, mut on_result: impl FnMut(solution::DayResult)) -> bool {
+    let tasks = select_tasks(&run.selection);
+    let mut failed = false;
+    pool::run_ordered(
+        &tasks,
+        run.jobs(),
+        |task| task.run(&run.input.source_for(task.day), run.extras()),
+        |task, result| match result {
+            Ok(result) => on_result(result),
+            Err(e) => {
+                eprintln!("Day {:2}: error: {}", task.day, e);
+                failed = true;
+            }
+        },
     );
+    failed
+}
+
+/// Prints the answers and solve times of one day. Returns whether any part failed.
+fn print_answers(result: &solution::DayResult) -> bool {
+    let mut failed = false;
+    for part in &result.parts {
+        match &part.answer {
+            Ok(answer) => println!(
+                "Day {:2}, Task {}: {:18} in {:12} us",
+                result.day,
+                part.part,
+                answer,
+                part.time.as_micros()
+            ),
+            Err(e) => {
+                eprintln!("Day {:2}, Task {}: error: {}", result.day, part.part, e);
+                failed = true;
+            }
+        }
+    }
+    failed
+}
+
+/// Compares the answers of one day with the expected ones. Returns the number of
+/// passed and failed parts.
+fn verify_answers(result: &solution::DayResult, expected: &answers::Answers) -> (usize, usize) {
+    let mut counts = (0, 0);
+    for part in &result.parts {
+        let prefix = format!("Day {:2}, Task {}:", result.day, part.part);
+        let actual = match &part.answer {
+            Ok(answer) => *answer,
+            Err(e) => {
+                println!("{} FAIL\n    error: {}", prefix, e);
+                counts.1 += 1;
+                continue;
+            }
+        };
+        match answers::check(expected.get(result.day, part.part), actual) {
+            answers::Verdict::Pass => {
+                println!("{} PASS {}", prefix, actual);
+                counts.0 += 1;
+            }
+            answers::Verdict::Fail { expected, actual } => {
+                println!(
+                    "{} FAIL\n    - expected: {}\n    + actual:   {}",
+                    prefix, expected, actual
+                );
+                counts.1 += 1;
+            }
+            answers::Verdict::Missing { actual } => {
+                println!(
+                    "{} FAIL\n    no expected answer, actual: {}",
+                    prefix, actual
+                );
+                counts.1 += 1;
+            }
+        }
+    }
+    counts
+}
+
+/// Writes what `content` gives for every result to the file `path` gives for the
+/// day, leaving out the days for which it gives `None`. `what` names the content,
+/// such as "a graph". Returns whether any day failed.
+fn write_day_files(
+    results: &[solution::DayResult],
+    what: &str,
+    path: impl Fn(u32) -> std::path::PathBuf,
+    content: impl Fn(&solution::DayResult) -> Result<Option<String>, &error::Error>,
+) -> bool {
+    let mut failed = false;
+    let mut written = 0;
+    for result in results {
+        let content = match content(result) {
+            Ok(Some(content)) => content,
+            Ok(None) => continue,
+            Err(e) => {
+                eprintln!("Day {:2}: error: {}", result.day, e);
+                failed = true;
+                continue;
+            }
+        };
+        let path = path(result.day);
+        if let Some(dir) = path.parent() {
+            if let Err(e) = std::fs::create_dir_all(dir) {
+                eprintln!("error: {}: {}", dir.display(), e);
+                failed = true;
+                continue;
+            }
+        }
+        match std::fs::write(&path, content) {
+            Ok(()) => {
+                eprintln!("Day {:2}: wrote {} to {}", result.day, what, path.display());
+                written += 1;
+            }
+            Err(e) => {
+                eprintln!("error: {}: {}", path.display(), e);
+                failed = true;
+            }
+        }
+    }
+    if written == 0 && !failed {
+        eprintln!("none of the selected days has {}", what);
+    }
+    failed
 }
 
 fn main() {
-    measure_time(day1::task1);
-    measure_time(day1::task2);
-    measure_time(day2::task1);
-    measure_time(day2::task2);
-    measure_time(day3::task1);
-    measure_time(day3::task2);
-    measure_time(day4::task1);
-    measure_time(day4::task2);
-    measure_time(day5::task1);
-    measure_time(day5::task2);
-    measure_time(day6::task1);
-    measure_time(day6::task2);
-    measure_time(day7::task1);
-    measure_time(day7::task2);
-    measure_time(day8::task1);
-    measure_time(day8::task2);
-    measure_time(day9::task1);
-    measure_time(day9::task2);
-    measure_time(day10::task1);
-    measure_time(day10::task2);
-    measure_time(day11::task1);
-    measure_time(day11::task2);
-    measure_time(day12::task1);
-    measure_time(day12::task2);
-    measure_time(day13::task1);
-    measure_time(day13::task2);
-    measure_time(day14::task1);
-    measure_time(day14::task2);
-    measure_time(day15::task1);
-    measure_time(day15::task2);
-    measure_time(day16::task1);
-    measure_time(day16::task2);
-    measure_time(day17::task1);
-    measure_time(day17::task2);
-    measure_time(day18::task1);
-    measure_time(day18::task2);
-    measure_time(day19::task1);
-    measure_time(day19::task2);
-    measure_time(day20::task1);
-    measure_time(day20::task2);
-    measure_time(day21::task1);
-    measure_time(day21::task2);
-    measure_time(day22::task1);
-    measure_time(day22::task2);
-    measure_time(day23::task1);
-    measure_time(day23::task2);
-    measure_time(day24::task1);
-    measure_time(day24::task2);
-    measure_time(day25::task1);
+    let command = match cli::parse_args(std::env::args().skip(1)) {
+        Ok(command) => command,
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    match command {
+        cli::Command::Help => println!("{}", cli::USAGE),
+        cli::Command::Run(run) => {
+            let mut results = Vec::new();
+            let mut part_failed = false;
+            let input_failed = run_days(&run, |result| {
+                if run.format == cli::Format::Table {
+                    part_failed |= print_answers(&result);
+                } else {
+                    part_failed |= result.parts.iter().any(|part| part.answer.is_err());
+                }
+                results.push(result);
+            });
+            match run.format {
+                cli::Format::Table => println!("\n{}", report::summary_table(&results)),
+                cli::Format::Json => println!("{}", report::json(&results)),
+                cli::Format::Csv => println!("{}", report::csv(&results)),
+            }
+            let graph_failed = run.dot.as_ref().is_some_and(|dir| {
+                write_day_files(
+                    &results,
+                    "a graph",
+                    |day| dir.join(format!("day{}.dot", day)),
+                    |result| {
+                        Ok(result
+                            .graph
+                            .as_ref()?
+                            .as_ref()
+                            .map(|graph| graph.to_string()))
+                    },
+                )
+            });
+            let explain_failed = run.explain.as_ref().is_some_and(|dir| {
+                write_day_files(
+                    &results,
+                    "an explanation",
+                    |day| dir.join(format!("day{}.txt", day)),
+                    |result| {
+                        let explanations: Vec<String> = result
+                            .parts
+                            .iter()
+                            .filter_map(|part| {
+                                let explanation = part.explanation.as_ref()?;
+                                Some(format!("Part {}: {}\n", part.part, explanation))
+                            })
+                            .collect();
+                        Ok((!explanations.is_empty()).then(|| explanations.concat()))
+                    },
+                )
+            });
+            let trace_failed = run.trace.as_ref().is_some_and(|trace| {
+                write_day_files(
+                    &results,
+                    "a trace",
+                    |_| trace.path.clone(),
+                    |result| {
+                        Ok(result
+                            .trace
+                            .as_ref()?
+                            .as_ref()
+                            .map(|t| t.write(trace.format)))
+                    },
+                )
+            });
+            if input_failed || part_failed || graph_failed || explain_failed || trace_failed {
+                std::process::exit(1);
+            }
+        }
+        cli::Command::Verify(run) => {
+            let expected = match answers::Answers::load(&run.answers_path()) {
+                Ok(expected) => expected,
+                Err(e) => {
+                    eprintln!("error: {}", e);
+                    std::process::exit(2);
+                }
+            };
+            let (mut passed, mut failed) = (0, 0);
+            let input_failed = run_days(&run, |result| {
+                let counts = verify_answers(&result, &expected);
+                passed += counts.0;
+                failed += counts.1;
+            });
+            println!("\n{} passed, {} failed", passed, failed);
+            if failed > 0 || input_failed {
+                std::process::exit(1);
+            }
+        }
+        cli::Command::Bench(run, bench) => {
+            let baseline = match &bench.baseline {
+                Some(path) => match bench::Baseline::load(path) {
+                    Ok(baseline) => Some(baseline),
+                    Err(e) => {
+                        eprintln!("error: {}", e);
+                        std::process::exit(2);
+                    }
+                },
+                None => None,
+            };
+            let mut measurements = Vec::new();
+            let mut failed = false;
+            for task in select_tasks(&run.selection) {
+                let input = run.input.source_for(task.day);
+                match bench::measure(&task, &input, bench.warmup, bench.runs) {
+                    Ok(measured) => measurements.extend(measured),
+                    Err(e) => {
+                        eprintln!("Day {:2}: error: {}", task.day, e);
+                        failed = true;
+                    }
+                }
+            }
+            let (table, regressions) =
+                bench::table(&measurements, baseline.as_ref(), bench.threshold);
+            println!("{}", table);
+            if let Some(path) = &bench.save_baseline {
+                let saved = bench::Baseline::from_measurements(&measurements).save(path);
+                if let Err(e) = saved {
+                    eprintln!("error: {}: {}", path.display(), e);
+                    std::process::exit(2);
+                }
+            }
+            if regressions > 0 {
+                println!(
+                    "\n{} regressions of more than {}%",
+                    regressions, bench.threshold
+                );
+            }
+            if failed || regressions > 0 {
+                std::process::exit(1);
+            }
+        }
+    }
 }
@@ -0,0 +1,138 @@
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub day: u32,
+    pub line: Option<usize>,
+    /// The offending token, `None` if the input ended before it.
+    pub token: Option<String>,
+    pub expected: String,
+}
+
+impl ParseError {
+    pub fn new(token: &str, expected: impl Into<String>) -> Self {
+        ParseError {
+            day: 0,
+            line: None,
+            token: Some(token.to_owned()),
+            expected: expected.into(),
+        }
+    }
+
+    pub fn missing(expected: impl Into<String>) -> Self {
+        ParseError {
+            day: 0,
+            line: None,
+            token: None,
+            expected: expected.into(),
+        }
+    }
+
+    /// Attaches the day and the 1-based input line to an error raised by a line parser.
+    pub fn at(self, day: u32, line: usize) -> Self {
+        ParseError {
+            day,
+            line: Some(line),
+            ..self
+        }
+    }
+
+    pub fn in_day(self, day: u32) -> Self {
+        ParseError { day, ..self }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "day {}", self.day)?;
+        if let Some(line) = self.line {
+            write!(f, ", line {}", line)?;
+        }
+        match &self.token {
+            Some(token) => write!(f, ": expected {}, found '{}'", self.expected, token),
+            None => write!(f, ": expected {}, found nothing", self.expected),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Parses every line with `f`, attaching the day and the line number to errors.
+/// `first_line` is the 1-based line number of `lines[0]` in the input.
+pub fn lines<T, F>(
+    day: u32,
+    first_line: usize,
+    lines: &[String],
+    f: F,
+) -> Result<Vec<T>, ParseError>
+where
+    F: Fn(&str) -> Result<T, ParseError>,
+{
+    lines
+        .iter()
+        .enumerate()
+        .map(|(index, line)| f(line).map_err(|e| e.at(day, first_line + index)))
+        .collect()
+}
+
+pub fn number<T: FromStr>(token: &str) -> Result<T, ParseError> {
+    token
+        .trim()
+        .parse()
+        .map_err(|_| ParseError::new(token, "a number"))
+}
+
+pub fn next<'a, I: Iterator<Item = &'a str>>(
+    iter: &mut I,
+    expected: &str,
+) -> Result<&'a str, ParseError> {
+    iter.next().ok_or_else(|| ParseError::missing(expected))
+}
+
+pub fn split_once<'a>(s: &'a str, delimiter: &str) -> Result<(&'a str, &'a str), ParseError> {
+    s.split_once(delimiter)
+        .ok_or_else(|| ParseError::new(s, format!("'{}'", delimiter)))
+}
+
+pub fn strip_prefix<'a>(s: &'a str, prefix: &str) -> Result<&'a str, ParseError> {
+    s.strip_prefix(prefix)
+        .ok_or_else(|| ParseError::new(s, format!("a token starting with '{}'", prefix)))
+}
+
+pub fn strip_suffix<'a>(s: &'a str, suffix: &str) -> Result<&'a str, ParseError> {
+    s.strip_suffix(suffix)
+        .ok_or_else(|| ParseError::new(s, format!("a token ending with '{}'", suffix)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn lines_report_day_and_line() {
+        let input = vec!["1".to_owned(), "2".to_owned(), "x".to_owned()];
+        let error = lines(4, 1, &input, number::<u32>).unwrap_err();
+        assert_eq!(error, ParseError::new("x", "a number").at(4, 3));
+        assert_eq!(
+            error.to_string(),
+            "day 4, line 3: expected a number, found 'x'"
+        );
+    }
+
+    #[test]
+    fn helpers() {
+        assert_eq!(number::<i64>(" -12 "), Ok(-12));
+        assert_eq!(split_once("a -> b", " -> "), Ok(("a", "b")));
+        assert!(split_once("a b", " -> ").is_err());
+        assert_eq!(strip_prefix("Game 1", "Game "), Ok("1"));
+        assert_eq!(strip_suffix("(#70c710)", ")"), Ok("(#70c710"));
+
+        let mut iter = "a".split(',');
+        assert_eq!(next(&mut iter, "a"), Ok("a"));
+        assert_eq!(
+            next(&mut iter, "a color").unwrap_err().to_string(),
+            "day 0: expected a color, found nothing"
+        );
+    }
+}
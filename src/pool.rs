@@ -0,0 +1,86 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+
+/// Calls `work` on every item using up to `jobs` threads and hands the results to
+/// `on_result` on the calling thread, in the order of `items` no matter in which
+/// order they finish. With a single job everything runs on the calling thread.
+pub fn run_ordered<T, R>(
+    items: &[T],
+    jobs: usize,
+    work: impl Fn(&T) -> R + Sync,
+    mut on_result: impl FnMut(&T, R),
+) where
+    T: Sync,
+    R: Send,
+{
+    let jobs = jobs.clamp(1, items.len().max(1));
+    if jobs == 1 {
+        for item in items {
+            on_result(item, work(item));
+        }
+        return;
+    }
+
+    let next = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel();
+    thread::scope(|scope| {
+        for _ in 0..jobs {
+            let sender = sender.clone();
+            let (next, work) = (&next, &work);
+            scope.spawn(move || loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some(item) = items.get(index) else {
+                    break;
+                };
+                if sender.send((index, work(item))).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(sender);
+
+        // Results that finished before an earlier item wait here for their turn.
+        let mut pending = BTreeMap::new();
+        let mut emitted = 0;
+        for (index, result) in receiver {
+            pending.insert(index, result);
+            while let Some(result) = pending.remove(&emitted) {
+                on_result(&items[emitted], result);
+                emitted += 1;
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn results_keep_the_item_order() {
+        let items: Vec<u64> = (0..20).collect();
+        for jobs in [1, 4, 100] {
+            let mut seen = Vec::new();
+            run_ordered(
+                &items,
+                jobs,
+                |&item| {
+                    // Let early items finish last.
+                    thread::sleep(Duration::from_millis(20 - item));
+                    item * item
+                },
+                |&item, result| seen.push((item, result)),
+            );
+            let expected: Vec<(u64, u64)> = items.iter().map(|&i| (i, i * i)).collect();
+            assert_eq!(seen, expected);
+        }
+    }
+
+    #[test]
+    fn no_items() {
+        run_ordered(&[] as &[u32], 4, |_| (), |_, _| panic!());
+    }
+}
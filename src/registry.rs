@@ -0,0 +1,147 @@
+use crate::cli::{CliError, Selection};
+use crate::error::Error;
+use crate::input::InputSource;
+use crate::solution::{self, DayResult, Extras, Solution};
+
+/// A registered puzzle, erased from its `Solution` type.
+pub struct Day {
+    pub day: u32,
+    pub parts: u32,
+    run: fn(&InputSource, &[u32], Extras) -> Result<DayResult, Error>,
+}
+
+const fn day<S: Solution>() -> Day {
+    Day {
+        day: S::DAY,
+        parts: S::PARTS,
+        run: solution::run::<S>,
+    }
+}
+
+pub const DAYS: &[Day] = &[
+    day::<crate::day1::Day1>(),
+    day::<crate::day2::Day2>(),
+    day::<crate::day3::Day3>(),
+    day::<crate::day4::Day4>(),
+    day::<crate::day5::Day5>(),
+    day::<crate::day6::Day6>(),
+    day::<crate::day7::Day7>(),
+    day::<crate::day8::Day8>(),
+    day::<crate::day9::Day9>(),
+    day::<crate::day10::Day10>(),
+    day::<crate::day11::Day11>(),
+    day::<crate::day12::Day12>(),
+    day::<crate::day13::Day13>(),
+    day::<crate::day14::Day14>(),
+    day::<crate::day15::Day15>(),
+    day::<crate::day16::Day16>(),
+    day::<crate::day17::Day17>(),
+    day::<crate::day18::Day18>(),
+    day::<crate::day19::Day19>(),
+    day::<crate::day20::Day20>(),
+    day::<crate::day21::Day21>(),
+    day::<crate::day22::Day22>(),
+    day::<crate::day23::Day23>(),
+    day::<crate::day24::Day24>(),
+    day::<crate::day25::Day25>(),
+];
+
+/// The parts of one registered puzzle selected for a run.
+pub struct Task {
+    pub day: u32,
+    pub parts: Vec<u32>,
+    run: fn(&InputSource, &[u32], Extras) -> Result<DayResult, Error>,
+}
+
+impl Task {
+    pub fn run(&self, input: &InputSource, extras: Extras) -> Result<DayResult, Error> {
+        (self.run)(input, &self.parts, extras)
+    }
+}
+
+fn find_day(day: u32) -> Option<&'static Day> {
+    DAYS.iter().find(|d| d.day == day)
+}
+
+pub fn select(selection: &Selection) -> Result<Vec<Task>, CliError> {
+    if let Some(days) = &selection.days {
+        for day in days.clone() {
+            let Some(found) = find_day(day) else {
+                return Err(CliError::new(format!(
+                    "unknown day {} (available days: 1..{})",
+                    day,
+                    DAYS.iter().map(|d| d.day).max().unwrap_or(0)
+                )));
+            };
+            // A range skips the days without the task, a single day has to have it.
+            if let Some(task) = selection.task {
+                if task == 0 || (task > found.parts && days.start() == days.end()) {
+                    return Err(CliError::new(format!("day {} has no task {}", day, task)));
+                }
+            }
+        }
+    }
+    Ok(DAYS
+        .iter()
+        .map(|day| Task {
+            day: day.day,
+            parts: (1..=day.parts)
+                .filter(|&part| selection.matches(day.day, part))
+                .collect(),
+            run: day.run,
+        })
+        .filter(|task| !task.parts.is_empty())
+        .collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn days_are_registered_in_order() {
+        let days: Vec<u32> = DAYS.iter().map(|d| d.day).collect();
+        assert_eq!(days, (1..=25).collect::<Vec<_>>());
+        assert_eq!(DAYS.iter().map(|d| d.parts).sum::<u32>(), 49);
+    }
+
+    #[test]
+    fn select_tasks() {
+        let selection = Selection {
+            days: Some(24..=25),
+            task: None,
+        };
+        let selected: Vec<(u32, Vec<u32>)> = select(&selection)
+            .unwrap()
+            .into_iter()
+            .map(|t| (t.day, t.parts))
+            .collect();
+        assert_eq!(selected, vec![(24, vec![1, 2]), (25, vec![1])]);
+
+        let selection = Selection {
+            days: None,
+            task: Some(2),
+        };
+        let selected = select(&selection).unwrap();
+        assert_eq!(selected.len(), 24);
+        assert!(selected.iter().all(|t| t.parts == vec![2]));
+
+        let selection = Selection {
+            days: Some(20..=25),
+            task: Some(2),
+        };
+        let selected: Vec<u32> = select(&selection).unwrap().iter().map(|t| t.day).collect();
+        assert_eq!(selected, vec![20, 21, 22, 23, 24]);
+
+        let selection = Selection {
+            days: Some(25..=25),
+            task: Some(2),
+        };
+        assert!(select(&selection).is_err());
+        let selection = Selection {
+            days: Some(20..=25),
+            task: Some(0),
+        };
+        assert!(select(&selection).is_err());
+    }
+}
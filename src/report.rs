@@ -0,0 +1,212 @@
+use std::time::Duration;
+
+use crate::solution::{DayResult, PartResult};
+
+fn micros(time: Option<Duration>) -> String {
+    match time {
+        Some(time) => time.as_micros().to_string(),
+        None => "-".to_owned(),
+    }
+}
+
+fn part_time(result: &DayResult, part: u32) -> Option<Duration> {
+    result.parts.iter().find(|p| p.part == part).map(|p| p.time)
+}
+
+fn sum(times: impl Iterator<Item = Option<Duration>>) -> Option<Duration> {
+    times
+        .flatten()
+        .fold(None, |acc, t| Some(acc.unwrap_or_default() + t))
+}
+
+fn row(cells: [&str; 5]) -> String {
+    format!(
+        "{:>5} | {:>10} | {:>10} | {:>10} | {:>10}",
+        cells[0], cells[1], cells[2], cells[3], cells[4]
+    )
+}
+
+fn timing_row(label: &str, times: [Option<Duration>; 4]) -> String {
+    let [parse, part1, part2, total] = times.map(micros);
+    row([label, &parse, &part1, &part2, &total])
+}
+
+/// A table of the parse, solve and total times of every day, in microseconds.
+pub fn summary_table(results: &[DayResult]) -> String {
+    let header = row(["Day", "Parse us", "Part 1 us", "Part 2 us", "Total us"]);
+    let separator = "-".repeat(header.len());
+
+    let mut lines = vec![header, separator.clone()];
+    for result in results {
+        lines.push(timing_row(
+            &result.day.to_string(),
+            [
+                Some(result.parse_time),
+                part_time(result, 1),
+                part_time(result, 2),
+                Some(result.total_time()),
+            ],
+        ));
+    }
+    lines.push(separator);
+    lines.push(timing_row(
+        "Total",
+        [
+            sum(results.iter().map(|r| Some(r.parse_time))),
+            sum(results.iter().map(|r| part_time(r, 1))),
+            sum(results.iter().map(|r| part_time(r, 2))),
+            sum(results.iter().map(|r| Some(r.total_time()))),
+        ],
+    ));
+    lines.join("\n")
+}
+
+/// The results of every part in day and part order, paired with their day.
+fn part_results(results: &[DayResult]) -> impl Iterator<Item = (&DayResult, &PartResult)> {
+    results
+        .iter()
+        .flat_map(|result| result.parts.iter().map(move |part| (result, part)))
+}
+
+pub fn json_string(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len() + 2);
+    escaped.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+/// The results as a JSON array with one object per part. A failed part has a
+/// null answer and an error message.
+pub fn json(results: &[DayResult]) -> String {
+    let objects: Vec<String> = part_results(results)
+        .map(|(result, part)| {
+            let answer = match &part.answer {
+                Ok(answer) => answer.to_string(),
+                Err(e) => format!("null, \"error\": {}", json_string(&e.to_string())),
+            };
+            format!(
+                "  {{\"day\": {}, \"part\": {}, \"answer\": {}, \"parse_us\": {}, \"solve_us\": {}}}",
+                result.day,
+                part.part,
+                answer,
+                result.parse_time.as_micros(),
+                part.time.as_micros()
+            )
+        })
+        .collect();
+    if objects.is_empty() {
+        return "[]".to_owned();
+    }
+    format!("[\n{}\n]", objects.join(",\n"))
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_owned()
+    }
+}
+
+/// The results as CSV with a header line and one record per part. A failed part
+/// has an empty answer and an error message.
+pub fn csv(results: &[DayResult]) -> String {
+    let mut lines = vec!["day,part,answer,parse_us,solve_us,error".to_owned()];
+    for (result, part) in part_results(results) {
+        let (answer, error) = match &part.answer {
+            Ok(answer) => (answer.to_string(), String::new()),
+            Err(e) => (String::new(), csv_field(&e.to_string())),
+        };
+        lines.push(format!(
+            "{},{},{},{},{},{}",
+            result.day,
+            part.part,
+            answer,
+            result.parse_time.as_micros(),
+            part.time.as_micros(),
+            error
+        ));
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parse::ParseError;
+    use crate::solution::Answer;
+
+    fn day(day: u32, parse: u64, parts: &[(u32, u64)]) -> DayResult {
+        DayResult {
+            day,
+            parse_time: Duration::from_micros(parse),
+            parts: parts
+                .iter()
+                .map(|&(part, time)| PartResult {
+                    part,
+                    answer: Ok(Answer(0)),
+                    explanation: None,
+                    time: Duration::from_micros(time),
+                })
+                .collect(),
+            graph: Ok(None),
+            trace: Ok(None),
+        }
+    }
+
+    #[test]
+    fn table_with_totals() {
+        let results = [day(24, 10, &[(1, 20), (2, 30)]), day(25, 5, &[(1, 100)])];
+        let separator = "-".repeat(57);
+        let expected = [
+            "  Day |   Parse us |  Part 1 us |  Part 2 us |   Total us",
+            &separator,
+            "   24 |         10 |         20 |         30 |         60",
+            "   25 |          5 |        100 |          - |        105",
+            &separator,
+            "Total |         15 |        120 |         30 |        165",
+        ];
+        assert_eq!(summary_table(&results), expected.join("\n"));
+    }
+
+    fn with_error(mut result: DayResult, part: u32) -> DayResult {
+        let error = ParseError::new("x,\"y\"", "a number").in_day(result.day);
+        result.parts[part as usize - 1].answer = Err(error.into());
+        result
+    }
+
+    #[test]
+    fn json_output() {
+        let results = [day(1, 10, &[(1, 20)]), with_error(day(2, 5, &[(1, 7)]), 1)];
+        let expected = [
+            "[",
+            r#"  {"day": 1, "part": 1, "answer": 0, "parse_us": 10, "solve_us": 20},"#,
+            r#"  {"day": 2, "part": 1, "answer": null, "error": "day 2: expected a number, found 'x,\"y\"'", "parse_us": 5, "solve_us": 7}"#,
+            "]",
+        ];
+        assert_eq!(json(&results), expected.join("\n"));
+        assert_eq!(json(&[]), "[]");
+    }
+
+    #[test]
+    fn csv_output() {
+        let results = [day(1, 10, &[(1, 20)]), with_error(day(2, 5, &[(1, 7)]), 1)];
+        let expected = [
+            "day,part,answer,parse_us,solve_us,error",
+            "1,1,0,10,20,",
+            r#"2,1,,5,7,"day 2: expected a number, found 'x,""y""'""#,
+        ];
+        assert_eq!(csv(&results), expected.join("\n"));
+    }
+}
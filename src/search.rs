@@ -0,0 +1,244 @@
+use std::cmp::Reverse;
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, HashMap};
+use std::hash::Hash;
+use std::ops::Add;
+
+/// A path found by [`shortest_path`], from a start state to the goal state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Path<S, C> {
+    pub states: Vec<S>,
+    pub cost: C,
+}
+
+struct Node<S, C> {
+    state: S,
+    cost: C,
+    parent: Option<usize>,
+    done: bool,
+}
+
+/// Dijkstra's algorithm, or A* with a `heuristic`, over states that are only known
+/// through their `successors` and the cost of the step to each of them. Costs
+/// above `max_cost` are never explored. Stops at the first state that `is_goal`
+/// and returns its index, leaving the explored nodes in `nodes`.
+fn search<S, C, I>(
+    starts: impl IntoIterator<Item = S>,
+    mut successors: impl FnMut(&S) -> I,
+    mut heuristic: impl FnMut(&S) -> C,
+    mut is_goal: impl FnMut(&S) -> bool,
+    max_cost: Option<C>,
+    nodes: &mut Vec<Node<S, C>>,
+) -> Option<usize>
+where
+    S: Clone + Eq + Hash,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (S, C)>,
+{
+    let mut indices: HashMap<S, usize> = HashMap::new();
+    let mut queue = BinaryHeap::new();
+    for start in starts {
+        if let Entry::Vacant(entry) = indices.entry(start.clone()) {
+            entry.insert(nodes.len());
+            queue.push(Reverse((heuristic(&start), C::default(), nodes.len())));
+            nodes.push(Node {
+                state: start,
+                cost: C::default(),
+                parent: None,
+                done: false,
+            });
+        }
+    }
+
+    while let Some(Reverse((_, cost, index))) = queue.pop() {
+        // A cheaper way to this state was queued after this one.
+        if nodes[index].done || cost > nodes[index].cost {
+            continue;
+        }
+        nodes[index].done = true;
+        if is_goal(&nodes[index].state) {
+            return Some(index);
+        }
+
+        for (next, step) in successors(&nodes[index].state) {
+            let next_cost = cost + step;
+            if max_cost.is_some_and(|max_cost| next_cost > max_cost) {
+                continue;
+            }
+            let next_index = match indices.entry(next) {
+                Entry::Occupied(entry) => {
+                    let node = &mut nodes[*entry.get()];
+                    if node.done || node.cost <= next_cost {
+                        continue;
+                    }
+                    node.cost = next_cost;
+                    node.parent = Some(index);
+                    *entry.get()
+                }
+                Entry::Vacant(entry) => {
+                    nodes.push(Node {
+                        state: entry.key().clone(),
+                        cost: next_cost,
+                        parent: Some(index),
+                        done: false,
+                    });
+                    *entry.insert(nodes.len() - 1)
+                }
+            };
+            let priority = next_cost + heuristic(&nodes[next_index].state);
+            queue.push(Reverse((priority, next_cost, next_index)));
+        }
+    }
+    None
+}
+
+/// The cheapest path from any of `starts` to a state that `is_goal`, with Dijkstra's
+/// algorithm. `successors` returns the states reachable in one step together with
+/// the cost of that step.
+#[cfg_attr(not(test), allow(dead_code))]
+pub fn shortest_path<S, C, I>(
+    starts: impl IntoIterator<Item = S>,
+    successors: impl FnMut(&S) -> I,
+    is_goal: impl FnMut(&S) -> bool,
+) -> Option<Path<S, C>>
+where
+    S: Clone + Eq + Hash,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (S, C)>,
+{
+    shortest_path_astar(starts, successors, |_| C::default(), is_goal)
+}
+
+/// Like [`shortest_path`], but A*: `heuristic` estimates the remaining cost to a
+/// goal. It must never overestimate it, and must not drop by more than the cost of
+/// a step, or the path may not be the cheapest one.
+pub fn shortest_path_astar<S, C, I>(
+    starts: impl IntoIterator<Item = S>,
+    successors: impl FnMut(&S) -> I,
+    heuristic: impl FnMut(&S) -> C,
+    is_goal: impl FnMut(&S) -> bool,
+) -> Option<Path<S, C>>
+where
+    S: Clone + Eq + Hash,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (S, C)>,
+{
+    let mut nodes = Vec::new();
+    let goal = search(starts, successors, heuristic, is_goal, None, &mut nodes)?;
+
+    let cost = nodes[goal].cost;
+    let mut states = Vec::new();
+    let mut index = Some(goal);
+    while let Some(i) = index {
+        states.push(nodes[i].state.clone());
+        index = nodes[i].parent;
+    }
+    states.reverse();
+    Some(Path { states, cost })
+}
+
+/// The cost of the cheapest path from any of `starts` to every reachable state,
+/// leaving out states that cost more than `max_cost`.
+pub fn distances<S, C, I>(
+    starts: impl IntoIterator<Item = S>,
+    successors: impl FnMut(&S) -> I,
+    max_cost: Option<C>,
+) -> HashMap<S, C>
+where
+    S: Clone + Eq + Hash,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (S, C)>,
+{
+    let mut nodes = Vec::new();
+    search(
+        starts,
+        successors,
+        |_| C::default(),
+        |_| false,
+        max_cost,
+        &mut nodes,
+    );
+    nodes
+        .into_iter()
+        .map(|node| (node.state, node.cost))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// A line of towns where the direct road to the next town costs 5 and the
+    /// road skipping one costs 7.
+    fn roads(&town: &u32) -> Vec<(u32, u32)> {
+        vec![(town + 1, 5), (town + 2, 7)]
+    }
+
+    #[test]
+    fn shortest_path_with_reconstruction() {
+        let path = shortest_path([0], roads, |&town| town == 4).unwrap();
+        assert_eq!(path.cost, 14);
+        assert_eq!(path.states, vec![0, 2, 4]);
+
+        let path = shortest_path([3, 0], roads, |&town| town == 4).unwrap();
+        assert_eq!(
+            path,
+            Path {
+                states: vec![3, 4],
+                cost: 5
+            }
+        );
+
+        assert_eq!(
+            shortest_path([0], |_: &u32| [(0, 1u32); 0], |&town| town == 1),
+            None
+        );
+    }
+
+    #[test]
+    fn astar_finds_the_same_cost() {
+        let grid = ["....", ".##.", "...#", "#..."];
+        let open = |(row, col): (usize, usize)| grid[row].as_bytes()[col] == b'.';
+        let successors = |&(row, col): &(usize, usize)| {
+            let mut next = vec![(row + 1, col), (row, col + 1)];
+            next.extend(row.checked_sub(1).map(|row| (row, col)));
+            next.extend(col.checked_sub(1).map(|col| (row, col)));
+            next.into_iter()
+                .filter(|&(row, col)| row < 4 && col < 4 && open((row, col)))
+                .map(|pos| (pos, 1))
+                .collect::<Vec<_>>()
+        };
+        let goal = (3, 3);
+        let is_goal = |&pos: &(usize, usize)| pos == goal;
+
+        let dijkstra = shortest_path([(0, 0)], successors, is_goal).unwrap();
+        let astar = shortest_path_astar(
+            [(0, 0)],
+            successors,
+            |&(row, col)| goal.0 - row + goal.1 - col,
+            is_goal,
+        )
+        .unwrap();
+        assert_eq!(dijkstra.cost, 6);
+        assert_eq!(astar.cost, 6);
+        assert_eq!(astar.states.len(), 7);
+        assert_eq!(astar.states.first(), Some(&(0, 0)));
+        assert_eq!(astar.states.last(), Some(&goal));
+    }
+
+    #[test]
+    fn bounded_distances() {
+        let all = distances(
+            [0],
+            |&town: &u32| roads(&town).into_iter().filter(|&(t, _)| t <= 4),
+            None,
+        );
+        assert_eq!(all.len(), 5);
+        assert_eq!(all[&4], 14);
+
+        let bounded = distances([0], roads, Some(12));
+        let mut towns: Vec<_> = bounded.into_iter().collect();
+        towns.sort();
+        assert_eq!(towns, vec![(0, 0), (1, 5), (2, 7), (3, 12)]);
+    }
+}
@@ -0,0 +1,205 @@
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::dot::Dot;
+use crate::error::Error;
+use crate::input::InputSource;
+use crate::trace::Trace;
+
+/// The answer to one part of a puzzle. Every puzzle of 2023 has an integer answer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Answer(pub i64);
+
+macro_rules! answer_from {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Answer {
+                fn from(value: $t) -> Self {
+                    Answer(i64::try_from(value).expect("answer does not fit into an i64"))
+                }
+            }
+        )*
+    };
+}
+
+answer_from!(i32, u32, i64, u64, i128, usize);
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+pub trait Solution {
+    const DAY: u32;
+    /// The number of parts of the puzzle. The last day only has one.
+    const PARTS: u32 = 2;
+
+    /// The puzzle input after parsing, shared by both parts.
+    type Input;
+
+    fn parse(input: &InputSource) -> Result<Self::Input, Error>;
+
+    fn part1(input: &Self::Input) -> Result<Answer, Error>;
+
+    fn part2(_input: &Self::Input) -> Result<Answer, Error> {
+        unreachable!("day {} has a single part", Self::DAY)
+    }
+
+    /// The input drawn as a graph, for the puzzles that are about one.
+    fn graph(_input: &Self::Input) -> Result<Option<Dot>, Error> {
+        Ok(None)
+    }
+
+    /// Solves `part` and explains how its answer comes about, for the puzzles that
+    /// can show it, such as the route taken.
+    fn explain(input: &Self::Input, part: u32) -> Result<(Answer, Option<String>), Error> {
+        Ok((solve::<Self>(input, part)?, None))
+    }
+
+    /// Every signal sent during `presses` presses of a button, for the puzzles about
+    /// a machine with one.
+    fn trace(_input: &Self::Input, _presses: u64) -> Result<Option<Trace>, Error> {
+        Ok(None)
+    }
+}
+
+/// What a run produces besides the answers, for the runner options that ask for
+/// it. All of it comes from the same parsed input as the answers.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Extras {
+    pub graph: bool,
+    /// Whether to explain the answers. The time of a part then includes it.
+    pub explain: bool,
+    /// The number of presses to trace, if any.
+    pub trace: Option<u64>,
+}
+
+pub struct PartResult {
+    pub part: u32,
+    pub answer: Result<Answer, Error>,
+    pub explanation: Option<String>,
+    pub time: Duration,
+}
+
+pub struct DayResult {
+    pub day: u32,
+    pub parse_time: Duration,
+    pub parts: Vec<PartResult>,
+    /// The graph of the input, if asked for and the puzzle has one.
+    pub graph: Result<Option<Dot>, Error>,
+    /// The trace of the input, if asked for and the puzzle has one.
+    pub trace: Result<Option<Trace>, Error>,
+}
+
+impl DayResult {
+    pub fn total_time(&self) -> Duration {
+        self.parse_time + self.parts.iter().map(|p| p.time).sum::<Duration>()
+    }
+}
+
+fn solve<S: Solution + ?Sized>(input: &S::Input, part: u32) -> Result<Answer, Error> {
+    match part {
+        1 => S::part1(input),
+        2 => S::part2(input),
+        _ => unreachable!("day {} has no part {}", S::DAY, part),
+    }
+}
+
+/// Parses `input` once and solves the given parts of the puzzle of `S` on it,
+/// timing the parse step and every part separately, along with the `extras`.
+pub fn run<S: Solution>(
+    input: &InputSource,
+    parts: &[u32],
+    extras: Extras,
+) -> Result<DayResult, Error> {
+    run_configured::<S>(input, parts, extras, |_| ())
+}
+
+/// Like [`run`], but lets `configure` change the parsed input before solving.
+fn run_configured<S: Solution>(
+    input: &InputSource,
+    parts: &[u32],
+    extras: Extras,
+    configure: impl FnOnce(&mut S::Input),
+) -> Result<DayResult, Error> {
+    let now = Instant::now();
+    let mut parsed = S::parse(input)?;
+    configure(&mut parsed);
+    let parse_time = now.elapsed();
+
+    let parts = parts
+        .iter()
+        .map(|&part| {
+            let now = Instant::now();
+            let (answer, explanation) = if extras.explain {
+                match S::explain(&parsed, part) {
+                    Ok((answer, explanation)) => (Ok(answer), explanation),
+                    Err(e) => (Err(e), None),
+                }
+            } else {
+                (solve::<S>(&parsed, part), None)
+            };
+            PartResult {
+                part,
+                answer,
+                explanation,
+                time: now.elapsed(),
+            }
+        })
+        .collect();
+
+    Ok(DayResult {
+        day: S::DAY,
+        parse_time,
+        parts,
+        graph: if extras.graph {
+            S::graph(&parsed)
+        } else {
+            Ok(None)
+        },
+        trace: match extras.trace {
+            Some(presses) => S::trace(&parsed, presses),
+            None => Ok(None),
+        },
+    })
+}
+
+/// Solves one part of the puzzle of `S` on an example input, going through the
+/// same parse and solve steps as the real input.
+#[cfg(test)]
+pub fn example<S: Solution>(text: &str, part: u32) -> Answer {
+    example_with::<S>(text, part, |_| ())
+}
+
+/// Like [`example`], but lets `configure` change the parsed input first, for the
+/// examples that use other parameters than the puzzle input, such as fewer steps.
+#[cfg(test)]
+pub fn example_with<S: Solution>(
+    text: &str,
+    part: u32,
+    configure: impl FnOnce(&mut S::Input),
+) -> Answer {
+    let input = InputSource::Text(text.to_owned());
+    let mut result = run_configured::<S>(&input, &[part], Extras::default(), configure).unwrap();
+    result.parts.remove(0).answer.unwrap()
+}
+
+/// Parses an example input of `S`, for the tests that look into the parsed input.
+#[cfg(test)]
+pub fn parse_example<S: Solution>(text: &str) -> S::Input {
+    S::parse(&InputSource::Text(text.to_owned())).unwrap()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn answer_conversions() {
+        assert_eq!(Answer::from(7_u32), Answer(7));
+        assert_eq!(Answer::from(-7_i32), Answer(-7));
+        assert_eq!(Answer::from(20220305520997_u64), Answer(20220305520997));
+        assert_eq!(format!("{:>5}", Answer(42)), "   42");
+    }
+}
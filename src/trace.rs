@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+
+use crate::report;
+
+/// How a trace is written.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TraceFormat {
+    /// One JSON object per line.
+    #[default]
+    JsonLines,
+    /// A Value Change Dump.
+    Vcd,
+}
+
+/// A signal sent from one module of a machine to another, such as a pulse.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Signal {
+    /// The press of the button the signal belongs to, counted from 1.
+    pub press: u64,
+    /// The position of the signal in the queue of its press, counted from 0.
+    pub position: usize,
+    pub source: String,
+    pub target: String,
+    pub high: bool,
+}
+
+/// Every signal of a number of presses, in the order they were processed.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Trace {
+    pub signals: Vec<Signal>,
+}
+
+impl Trace {
+    pub fn write(&self, format: TraceFormat) -> String {
+        match format {
+            TraceFormat::JsonLines => self.to_json_lines(),
+            TraceFormat::Vcd => self.to_vcd(),
+        }
+    }
+
+    /// One JSON object per line and signal.
+    fn to_json_lines(&self) -> String {
+        let mut s = String::new();
+        for signal in &self.signals {
+            s.push_str(&format!(
+                "{{\"press\": {}, \"position\": {}, \"source\": {}, \"target\": {}, \"pulse\": \"{}\"}}\n",
+                signal.press,
+                signal.position,
+                report::json_string(&signal.source),
+                report::json_string(&signal.target),
+                if signal.high { "high" } else { "low" }
+            ));
+        }
+        s
+    }
+
+    /// A Value Change Dump for waveform viewers. Every module that sends signals
+    /// is a wire showing its last one, next to a counter of the presses. Each
+    /// signal takes one time unit.
+    fn to_vcd(&self) -> String {
+        let mut sources: Vec<&str> = self
+            .signals
+            .iter()
+            .map(|signal| signal.source.as_str())
+            .collect();
+        sources.sort();
+        sources.dedup();
+        let ids: HashMap<&str, String> = sources
+            .iter()
+            .enumerate()
+            .map(|(i, &source)| (source, vcd_id(i + 1)))
+            .collect();
+
+        let mut s = String::new();
+        s.push_str("$version aoc-2023 $end\n");
+        s.push_str("$timescale 1ns $end\n");
+        s.push_str("$scope module network $end\n");
+        s.push_str(&format!("$var integer 64 {} press $end\n", vcd_id(0)));
+        for source in &sources {
+            s.push_str(&format!("$var wire 1 {} {} $end\n", ids[source], source));
+        }
+        s.push_str("$upscope $end\n$enddefinitions $end\n");
+        s.push_str("#0\n$dumpvars\n");
+        s.push_str(&format!("b0 {}\n", vcd_id(0)));
+        for source in &sources {
+            s.push_str(&format!("0{}\n", ids[source]));
+        }
+        s.push_str("$end\n");
+
+        let mut press = 0;
+        let mut levels: HashMap<&str, bool> = HashMap::new();
+        for (time, signal) in self.signals.iter().enumerate() {
+            let level = levels.entry(&signal.source).or_insert(false);
+            if signal.press == press && *level == signal.high {
+                continue;
+            }
+            s.push_str(&format!("#{}\n", time + 1));
+            if signal.press != press {
+                press = signal.press;
+                s.push_str(&format!("b{:b} {}\n", press, vcd_id(0)));
+            }
+            if *level != signal.high {
+                *level = signal.high;
+                let value = if signal.high { '1' } else { '0' };
+                s.push_str(&format!("{}{}\n", value, ids[signal.source.as_str()]));
+            }
+        }
+        s
+    }
+}
+
+/// The `index`th short identifier of a VCD variable, made of printable characters.
+fn vcd_id(mut index: usize) -> String {
+    let mut id = String::new();
+    loop {
+        id.push((b'!' + (index % 94) as u8) as char);
+        index /= 94;
+        if index == 0 {
+            return id;
+        }
+        index -= 1;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn signal(press: u64, position: usize, source: &str, high: bool) -> Signal {
+        Signal {
+            press,
+            position,
+            source: source.to_owned(),
+            target: "out".to_owned(),
+            high,
+        }
+    }
+
+    #[test]
+    fn json_lines() {
+        let trace = Trace {
+            signals: vec![signal(1, 0, "a\"b", true)],
+        };
+        assert_eq!(
+            trace.write(TraceFormat::JsonLines),
+            "{\"press\": 1, \"position\": 0, \"source\": \"a\\\"b\", \"target\": \"out\", \"pulse\": \"high\"}\n"
+        );
+    }
+
+    #[test]
+    fn vcd() {
+        let trace = Trace {
+            signals: vec![
+                signal(1, 0, "a", false),
+                signal(1, 1, "a", true),
+                signal(2, 0, "a", true),
+            ],
+        };
+        let vcd = trace.write(TraceFormat::Vcd);
+        assert!(vcd.starts_with("$version aoc-2023 $end\n"));
+        assert!(
+            vcd.ends_with("$end\n#1\nb1 !\n#2\n1\"\n#3\nb10 !\n"),
+            "{}",
+            vcd
+        );
+
+        assert_eq!(vcd_id(93), "~");
+        assert_eq!(vcd_id(94), "!!");
+        assert_eq!(vcd_id(95), "\"!");
+    }
+}